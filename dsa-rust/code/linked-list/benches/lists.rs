@@ -0,0 +1,105 @@
+//! The arena list against the pointer based lists. Run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use linked_list::{ArenaLinkedList, BoxSingleLinkedList, LinkedListT, RcSingleLinkedList};
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+fn build<L: LinkedListT<Item = usize>>(n: usize) -> L {
+    let mut list = L::new();
+    for i in 0..n {
+        list.push_front(i);
+    }
+    list
+}
+
+fn push_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_front then pop_front");
+    for n in SIZES {
+        group.bench_with_input(BenchmarkId::new("box", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = build::<BoxSingleLinkedList<usize>>(n);
+                while let Some(v) = list.pop_front() {
+                    black_box(v);
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("rc", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = build::<RcSingleLinkedList<usize>>(n);
+                while let Some(v) = list.pop_front() {
+                    black_box(v);
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("arena", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = build::<ArenaLinkedList<usize>>(n);
+                while let Some(v) = list.pop_front() {
+                    black_box(v);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sum by iteration");
+    for n in SIZES {
+        let list = build::<BoxSingleLinkedList<usize>>(n);
+        group.bench_with_input(BenchmarkId::new("box", n), &list, |b, list| {
+            b.iter(|| list.iter().sum::<usize>())
+        });
+        let list = build::<RcSingleLinkedList<usize>>(n);
+        group.bench_with_input(BenchmarkId::new("rc", n), &list, |b, list| {
+            b.iter(|| list.iter().map(|node| *node.borrow()).sum::<usize>())
+        });
+        let list = build::<ArenaLinkedList<usize>>(n);
+        group.bench_with_input(BenchmarkId::new("arena", n), &list, |b, list| {
+            b.iter(|| list.iter().sum::<usize>())
+        });
+    }
+    group.finish();
+}
+
+/// Removing every other element: the arena goes straight to the node
+/// through a handle, the others have to search for each value. Quadratic
+/// for them, so the largest size is left out.
+fn remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("remove every other element");
+    for &n in &SIZES[..2] {
+        group.bench_with_input(BenchmarkId::new("box", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = build::<BoxSingleLinkedList<usize>>(n);
+                for v in (0..n).step_by(2) {
+                    list.remove(&v);
+                }
+                list
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("rc", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = build::<RcSingleLinkedList<usize>>(n);
+                for v in (0..n).step_by(2) {
+                    list.remove(&v);
+                }
+                list
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("arena", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = ArenaLinkedList::new();
+                let handles: Vec<_> = (0..n).map(|v| list.push_front(v)).collect();
+                for handle in handles.into_iter().step_by(2) {
+                    list.remove_handle(handle);
+                }
+                list
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, push_pop, iterate, remove);
+criterion_main!(benches);
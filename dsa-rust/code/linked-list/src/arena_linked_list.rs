@@ -0,0 +1,595 @@
+//! A doubly linked list whose nodes live in a `Vec` and link to each other
+//! by index.
+//!
+//! There is no reference counting, no `RefCell` and no `unsafe`: a link is
+//! just a slot number. Removed slots go on a free list and are reused by
+//! the next insert, so the `Vec` only grows when every slot is taken.
+//!
+//! Inserting hands out a [`Handle`] to the new element. It stays valid
+//! until that element is removed, wherever the element ends up in the list,
+//! and gives O(1) access, insertion next to it and removal. Each slot
+//! counts how often it has been freed, and a handle remembers the count
+//! from when it was made, so a handle to a removed element is recognized
+//! even after its slot was reused.
+
+mod lfu_cache;
+mod lru_cache;
+
+use std::fmt;
+use std::mem;
+
+use crate::{Layout, LinkedListT, Location, NodeLayout, Pointer, PointerKind, Root};
+
+pub use lfu_cache::LfuCache;
+pub use lru_cache::{CacheStats, Clock, LruCache, ManualClock, SystemClock};
+
+/// A stable reference to one element of a [`LinkedList`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    index: usize,
+    generation: u32,
+}
+
+struct Node<T> {
+    value: T,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+enum Slot<T> {
+    Occupied(Node<T>),
+    Free { next_free: Option<usize> },
+}
+
+struct Entry<T> {
+    generation: u32,
+    slot: Slot<T>,
+}
+
+pub struct LinkedList<T> {
+    entries: Vec<Entry<T>>,
+    free: Option<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    len: usize,
+}
+
+impl<T> LinkedList<T> {
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            free: None,
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// How many slots the arena has, taken or free.
+    pub fn slots(&self) -> usize {
+        self.entries.len()
+    }
+    fn node(&self, index: usize) -> &Node<T> {
+        match &self.entries[index].slot {
+            Slot::Occupied(node) => node,
+            Slot::Free { .. } => unreachable!("link to a free slot"),
+        }
+    }
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        match &mut self.entries[index].slot {
+            Slot::Occupied(node) => node,
+            Slot::Free { .. } => unreachable!("link to a free slot"),
+        }
+    }
+    /// The slot `handle` points at, if it still holds the same element.
+    fn resolve(&self, handle: Handle) -> Option<usize> {
+        let entry = self.entries.get(handle.index)?;
+        match entry.slot {
+            Slot::Occupied(_) if entry.generation == handle.generation => Some(handle.index),
+            _ => None,
+        }
+    }
+    fn handle(&self, index: usize) -> Handle {
+        Handle {
+            index,
+            generation: self.entries[index].generation,
+        }
+    }
+    /// Puts `value` in a free slot, or a new one, and links it between
+    /// `prev` and `next`, which have to be neighbours.
+    fn link(&mut self, prev: Option<usize>, next: Option<usize>, value: T) -> Handle {
+        let node = Slot::Occupied(Node { value, prev, next });
+        let index = match self.free {
+            Some(index) => {
+                let entry = &mut self.entries[index];
+                let Slot::Free { next_free } = mem::replace(&mut entry.slot, node) else {
+                    unreachable!("free list points at a taken slot");
+                };
+                self.free = next_free;
+                index
+            }
+            None => {
+                self.entries.push(Entry {
+                    generation: 0,
+                    slot: node,
+                });
+                self.entries.len() - 1
+            }
+        };
+        self.attach(index, prev, next);
+        self.len += 1;
+        self.handle(index)
+    }
+    /// Links the node in slot `index` between `prev` and `next`, which
+    /// have to be neighbours.
+    fn attach(&mut self, index: usize, prev: Option<usize>, next: Option<usize>) {
+        let node = self.node_mut(index);
+        node.prev = prev;
+        node.next = next;
+        match prev {
+            Some(prev) => self.node_mut(prev).next = Some(index),
+            None => self.head = Some(index),
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = Some(index),
+            None => self.tail = Some(index),
+        }
+    }
+    /// Links `prev` and `next`, the neighbours of a node that leaves its
+    /// place, to each other. The node itself stays in its slot.
+    fn detach(&mut self, prev: Option<usize>, next: Option<usize>) {
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+    /// Unlinks the node in slot `index` and puts the slot on the free list.
+    fn unlink(&mut self, index: usize) -> T {
+        let entry = &mut self.entries[index];
+        let slot = mem::replace(
+            &mut entry.slot,
+            Slot::Free {
+                next_free: self.free,
+            },
+        );
+        let Slot::Occupied(node) = slot else {
+            unreachable!("unlinking a free slot");
+        };
+        // every handle to the old element is stale from here on
+        entry.generation = entry.generation.wrapping_add(1);
+        self.free = Some(index);
+        self.detach(node.prev, node.next);
+        self.len -= 1;
+        node.value
+    }
+    pub fn push_front(&mut self, value: T) -> Handle {
+        self.link(None, self.head, value)
+    }
+    pub fn push_back(&mut self, value: T) -> Handle {
+        self.link(self.tail, None, value)
+    }
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head?;
+        Some(self.unlink(head))
+    }
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail?;
+        Some(self.unlink(tail))
+    }
+    /// Inserts `value` right after the element of `handle`. `None` if the
+    /// handle is stale.
+    pub fn insert_after(&mut self, handle: Handle, value: T) -> Option<Handle> {
+        let index = self.resolve(handle)?;
+        let next = self.node(index).next;
+        Some(self.link(Some(index), next, value))
+    }
+    /// Inserts `value` right before the element of `handle`. `None` if the
+    /// handle is stale.
+    pub fn insert_before(&mut self, handle: Handle, value: T) -> Option<Handle> {
+        let index = self.resolve(handle)?;
+        let prev = self.node(index).prev;
+        Some(self.link(prev, Some(index), value))
+    }
+    /// Moves the element of `handle` to the front, the handle stays valid.
+    /// Returns whether the handle was.
+    pub fn move_to_front(&mut self, handle: Handle) -> bool {
+        let Some(index) = self.resolve(handle) else {
+            return false;
+        };
+        if self.head != Some(index) {
+            let node = self.node(index);
+            self.detach(node.prev, node.next);
+            self.attach(index, None, self.head);
+        }
+        true
+    }
+    /// Moves the element of `handle` to the back, the handle stays valid.
+    /// Returns whether the handle was.
+    pub fn move_to_back(&mut self, handle: Handle) -> bool {
+        let Some(index) = self.resolve(handle) else {
+            return false;
+        };
+        if self.tail != Some(index) {
+            let node = self.node(index);
+            self.detach(node.prev, node.next);
+            self.attach(index, self.tail, None);
+        }
+        true
+    }
+    /// Removes the element of `handle`. `None` if the handle is stale.
+    pub fn remove_handle(&mut self, handle: Handle) -> Option<T> {
+        let index = self.resolve(handle)?;
+        Some(self.unlink(index))
+    }
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        let index = self.resolve(handle)?;
+        Some(&self.node(index).value)
+    }
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        let index = self.resolve(handle)?;
+        Some(&mut self.node_mut(index).value)
+    }
+    /// Whether `handle` still refers to an element of this list.
+    pub fn contains_handle(&self, handle: Handle) -> bool {
+        self.resolve(handle).is_some()
+    }
+    pub fn front(&self) -> Option<Handle> {
+        self.head.map(|index| self.handle(index))
+    }
+    pub fn back(&self) -> Option<Handle> {
+        self.tail.map(|index| self.handle(index))
+    }
+    /// The handle of the element after the one of `handle`.
+    pub fn next(&self, handle: Handle) -> Option<Handle> {
+        let index = self.resolve(handle)?;
+        self.node(index).next.map(|next| self.handle(next))
+    }
+    /// The handle of the element before the one of `handle`.
+    pub fn prev(&self, handle: Handle) -> Option<Handle> {
+        let index = self.resolve(handle)?;
+        self.node(index).prev.map(|prev| self.handle(prev))
+    }
+    /// Walks from whichever end is closer to `index`.
+    fn index_at(&self, index: usize) -> Option<usize> {
+        if index >= self.len {
+            return None;
+        }
+        if index <= self.len / 2 {
+            let mut current = self.head;
+            for _ in 0..index {
+                current = self.node(current?).next;
+            }
+            current
+        } else {
+            let mut current = self.tail;
+            for _ in index + 1..self.len {
+                current = self.node(current?).prev;
+            }
+            current
+        }
+    }
+    /// The handle of the element at position `index`. O(min(index, len - index)).
+    pub fn handle_at(&self, index: usize) -> Option<Handle> {
+        self.index_at(index).map(|index| self.handle(index))
+    }
+    /// Removes every element. The slots are kept for reuse and every
+    /// handle into the list becomes stale.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "None <-> ")?;
+        for value in self {
+            write!(f, "{} <-> ", value)?;
+        }
+        write!(f, "None")
+    }
+}
+
+pub struct Iter<'a, T> {
+    list: &'a LinkedList<T>,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.front?);
+        self.front = node.next;
+        self.len -= 1;
+        Some(&node.value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.list.node(self.back?);
+        self.back = node.prev;
+        self.len -= 1;
+        Some(&node.value)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: fmt::Display + PartialEq> LinkedListT for LinkedList<T> {
+    type Item = T;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn contains(&self, value: &Self::Item) -> bool {
+        self.iter().any(|v| v == value)
+    }
+    fn get(&self, index: usize) -> Option<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        let index = self.index_at(index)?;
+        Some(self.node(index).value.clone())
+    }
+    fn to_vec(&self) -> Vec<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.iter().cloned().collect()
+    }
+    fn push_front(&mut self, value: Self::Item) {
+        self.push_front(value);
+    }
+    fn push_back(&mut self, value: Self::Item) {
+        self.push_back(value);
+    }
+    fn pop_front(&mut self) -> Option<Self::Item> {
+        self.pop_front()
+    }
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        self.pop_back()
+    }
+    fn insert_at(&mut self, index: usize, value: Self::Item) {
+        assert!(index <= self.len, "index {} out of bounds", index);
+        match self.index_at(index) {
+            Some(next) => {
+                let prev = self.node(next).prev;
+                self.link(prev, Some(next), value);
+            }
+            None => {
+                self.push_back(value);
+            }
+        }
+    }
+    fn remove_at(&mut self, index: usize) -> Option<Self::Item> {
+        let index = self.index_at(index)?;
+        Some(self.unlink(index))
+    }
+    fn remove(&mut self, value: &Self::Item) -> bool {
+        let mut current = self.head;
+        while let Some(index) = current {
+            let node = self.node(index);
+            if node.value == *value {
+                self.unlink(index);
+                return true;
+            }
+            current = node.next;
+        }
+        false
+    }
+    fn update(&mut self, old_val: &Self::Item, new_val: Self::Item) -> Option<Self::Item> {
+        let mut current = self.head;
+        while let Some(index) = current {
+            let node = self.node_mut(index);
+            if node.value == *old_val {
+                return Some(mem::replace(&mut node.value, new_val));
+            }
+            current = node.next;
+        }
+        None
+    }
+    fn clear(&mut self) {
+        self.clear();
+    }
+    /// Nodes are located by their slot, links are indices into the arena.
+    fn layout(&self) -> Layout {
+        let pointer = |index: Option<usize>| {
+            index.map(|index| Pointer::new(PointerKind::Index, Location::Slot(index)))
+        };
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut current = self.head;
+        while let Some(index) = current {
+            let node = self.node(index);
+            let mut layout = NodeLayout::new(Location::Slot(index), &node.value);
+            layout.next = pointer(node.next);
+            layout.prev = pointer(node.prev);
+            nodes.push(layout);
+            current = node.next;
+        }
+        Layout {
+            roots: vec![
+                Root {
+                    name: "head",
+                    link: pointer(self.head),
+                },
+                Root {
+                    name: "tail",
+                    link: pointer(self.tail),
+                },
+            ],
+            nodes,
+        }
+    }
+    fn header(&self) -> &str {
+        "--- Double Linked List (Arena) CLI ---"
+    }
+}
+
+#[cfg(test)]
+impl<T: fmt::Display + PartialEq + Clone> crate::tests::Snapshot for LinkedList<T> {
+    fn snapshot(&self) -> Vec<T> {
+        let values: Vec<T> = self.iter().cloned().collect();
+        let mut reversed: Vec<T> = self.iter().rev().cloned().collect();
+        reversed.reverse();
+        assert!(values == reversed, "prev links are out of sync");
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+
+    fn values(list: &LinkedList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_handles() {
+        let mut list = LinkedList::new();
+        let two = list.push_back(2);
+        let one = list.push_front(1);
+        let four = list.push_back(4);
+        let three = list.insert_before(four, 3).unwrap();
+        list.insert_after(four, 5).unwrap();
+        assert_eq!(values(&list), vec![1, 2, 3, 4, 5]);
+        // handles follow their element, not a position
+        *list.get_mut(three).unwrap() = 30;
+        assert_eq!(list.remove_handle(one), Some(1));
+        assert_eq!(list.get(three), Some(&30));
+        assert_eq!(list.next(two), Some(three));
+        assert_eq!(list.prev(two), None);
+        assert_eq!(list.front(), Some(two));
+        assert_eq!(list.handle_at(1), Some(three));
+        assert_eq!(values(&list), vec![2, 30, 4, 5]);
+    }
+
+    #[test]
+    fn test_stale_handles() {
+        let mut list = LinkedList::new();
+        let a = list.push_back(1);
+        assert_eq!(list.remove_handle(a), Some(1));
+        assert_eq!(list.remove_handle(a), None);
+        // `b` reuses the slot of `a`, but `a` must not see it
+        let b = list.push_back(2);
+        assert_eq!(list.slots(), 1);
+        assert!(!list.contains_handle(a));
+        assert_eq!(list.get(a), None);
+        assert_eq!(list.get_mut(a), None);
+        assert_eq!(list.insert_after(a, 3), None);
+        assert_eq!(list.insert_before(a, 3), None);
+        assert_eq!(list.next(a), None);
+        assert_eq!(list.get(b), Some(&2));
+        list.clear();
+        assert!(!list.contains_handle(b));
+    }
+
+    #[test]
+    fn test_move() {
+        let mut list = LinkedList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+        assert!(list.move_to_front(two));
+        assert_eq!(values(&list), vec![2, 1, 3]);
+        assert!(list.move_to_front(two));
+        assert!(list.move_to_back(one));
+        assert_eq!(values(&list), vec![2, 3, 1]);
+        assert!(list.move_to_back(two));
+        assert_eq!(values(&list), vec![3, 1, 2]);
+        assert_eq!(
+            list.iter().rev().copied().collect::<Vec<_>>(),
+            vec![2, 1, 3]
+        );
+        assert_eq!(list.get(three), Some(&3));
+        list.remove_handle(one);
+        assert!(!list.move_to_front(one));
+        assert_eq!(list.slots(), 3);
+    }
+
+    #[test]
+    fn test_free_list_reuse() {
+        let mut list: LinkedList<i32> = (0..8).collect();
+        for _ in 0..4 {
+            list.pop_front();
+        }
+        list.extend(8..12);
+        assert_eq!(list.slots(), 8);
+        list.push_back(12);
+        assert_eq!(list.slots(), 9);
+        assert_eq!(values(&list), (4..13).collect::<Vec<_>>());
+        assert_eq!(list.iter().next_back(), Some(&12));
+    }
+}
@@ -0,0 +1,440 @@
+//! A least frequently used cache, in O(1) per operation.
+//!
+//! The keys are grouped in buckets by how often they were used, and the
+//! buckets sit in an arena list in ascending order of that count. Each
+//! bucket is itself an arena list of its keys, the most recently used at
+//! the front. A use moves a key from its bucket to the front of the next
+//! one, which is either the neighbour or a new bucket inserted right after
+//! it, so nothing is ever searched. A full cache evicts from the back of
+//! the first bucket: the least recently used of the least used keys.
+//!
+//! ```text
+//! buckets: [1: d c] <-> [2: a] <-> [5: b]
+//!                 ^ evicted next
+//! ```
+//!
+//! Time to live and stats work as in [`LruCache`](super::LruCache).
+
+use std::{borrow::Borrow, collections::HashMap, hash::Hash, mem, time::Duration};
+
+use super::lru_cache::expired;
+use super::{CacheStats, Clock, Handle, LinkedList, SystemClock};
+
+struct Bucket<K> {
+    uses: u64,
+    /// Most recently used at the front.
+    keys: LinkedList<K>,
+}
+
+struct Entry<V> {
+    value: V,
+    expires: Option<Duration>,
+    /// The bucket of the key, and the key in it.
+    bucket: Handle,
+    key: Handle,
+}
+
+pub struct LfuCache<K, V, C = SystemClock> {
+    /// Ascending by `uses`, there is no empty bucket.
+    buckets: LinkedList<Bucket<K>>,
+    entries: HashMap<K, Entry<V>>,
+    capacity: usize,
+    clock: C,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    /// An empty cache for up to `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity == 0`.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_clock(capacity, SystemClock::default())
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, C: Clock> LfuCache<K, V, C> {
+    /// An empty cache for up to `capacity` entries whose time to live is
+    /// measured by `clock`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity == 0`.
+    pub fn with_clock(capacity: usize, clock: C) -> Self {
+        assert!(capacity > 0, "the capacity has to be at least 1");
+        Self {
+            buckets: LinkedList::new(),
+            entries: HashMap::with_capacity(capacity),
+            capacity,
+            clock,
+            stats: CacheStats::default(),
+        }
+    }
+    /// The number of entries, counting expired ones that weren't dropped yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+    fn bucket(&self, handle: Handle) -> &Bucket<K> {
+        self.buckets
+            .get(handle)
+            .expect("entries only point at live buckets")
+    }
+    fn bucket_mut(&mut self, handle: Handle) -> &mut Bucket<K> {
+        self.buckets
+            .get_mut(handle)
+            .expect("entries only point at live buckets")
+    }
+    /// Takes `key` out of `bucket`, and the bucket out of the list if that
+    /// was its last key.
+    fn unlink(&mut self, bucket: Handle, key: Handle) -> K {
+        let keys = &mut self.bucket_mut(bucket).keys;
+        let key = keys
+            .remove_handle(key)
+            .expect("entries only point at live keys");
+        if keys.is_empty() {
+            self.buckets.remove_handle(bucket);
+        }
+        key
+    }
+    /// Removes the entry for `key`, live or not, from the map and its bucket.
+    fn take<Q>(&mut self, key: &Q) -> Option<Entry<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entries.remove(key)?;
+        self.unlink(entry.bucket, entry.key);
+        Some(entry)
+    }
+    /// Whether there is a live entry for `key`. An expired entry is dropped
+    /// on the way.
+    fn find<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(entry) = self.entries.get(key) else {
+            return false;
+        };
+        if expired(&self.clock, entry.expires) {
+            self.take(key);
+            self.stats.expirations += 1;
+            return false;
+        }
+        true
+    }
+    /// Counts one more use of the live entry for `key` by moving it on to
+    /// the next bucket.
+    fn touch<Q>(&mut self, key: &Q) -> &mut Entry<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = &self.entries[key];
+        let (from, key_handle) = (entry.bucket, entry.key);
+        let uses = self.bucket(from).uses + 1;
+        let to = match self.buckets.next(from) {
+            Some(next) if self.bucket(next).uses == uses => next,
+            _ => self
+                .buckets
+                .insert_after(
+                    from,
+                    Bucket {
+                        uses,
+                        keys: LinkedList::new(),
+                    },
+                )
+                .expect("entries only point at live buckets"),
+        };
+        let moved = self.unlink(from, key_handle);
+        let key_handle = self.bucket_mut(to).keys.push_front(moved);
+        let entry = self.entries.get_mut(key).expect("the entry was found");
+        entry.bucket = to;
+        entry.key = key_handle;
+        entry
+    }
+    /// Like [`find`](Self::find) followed by [`touch`](Self::touch), and
+    /// counts a hit or a miss.
+    fn lookup<Q>(&mut self, key: &Q) -> Option<&mut Entry<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.find(key) {
+            self.stats.hits += 1;
+            Some(self.touch(key))
+        } else {
+            self.stats.misses += 1;
+            None
+        }
+    }
+    /// The value for `key`, counting one more use of it.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lookup(key).map(|entry| &entry.value)
+    }
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lookup(key).map(|entry| &mut entry.value)
+    }
+    /// The value for `key` without using it: the counts and the stats stay
+    /// as they are.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entries.get(key)?;
+        (!expired(&self.clock, entry.expires)).then_some(&entry.value)
+    }
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.peek(key).is_some()
+    }
+    /// How often the live entry for `key` was used, putting it counts as
+    /// the first use.
+    pub fn uses<Q>(&self, key: &Q) -> Option<u64>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entries.get(key)?;
+        (!expired(&self.clock, entry.expires)).then(|| self.bucket(entry.bucket).uses)
+    }
+    /// Stores `value` for `key` until it is evicted, returning the value
+    /// it replaced. Replacing a value counts as a use of the key, a new
+    /// key in a full cache evicts the least frequently used entry.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, None)
+    }
+    /// Like [`put`](Self::put), but the entry expires after `ttl`.
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let expires = self.clock.now() + ttl;
+        self.insert(key, value, Some(expires))
+    }
+    fn insert(&mut self, key: K, value: V, expires: Option<Duration>) -> Option<V> {
+        if self.find(&key) {
+            let entry = self.touch(&key);
+            entry.expires = expires;
+            return Some(mem::replace(&mut entry.value, value));
+        }
+        if self.entries.len() == self.capacity {
+            self.evict();
+        }
+        let bucket = match self.buckets.front() {
+            Some(front) if self.bucket(front).uses == 1 => front,
+            _ => self.buckets.push_front(Bucket {
+                uses: 1,
+                keys: LinkedList::new(),
+            }),
+        };
+        let key_handle = self.bucket_mut(bucket).keys.push_front(key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                expires,
+                bucket,
+                key: key_handle,
+            },
+        );
+        None
+    }
+    /// Takes out the least recently used of the least used entries.
+    fn pop(&mut self) -> Option<(K, Entry<V>)> {
+        let front = self.buckets.front()?;
+        let key_handle = self
+            .bucket(front)
+            .keys
+            .back()
+            .expect("there is no empty bucket");
+        let key = self.bucket(front).keys.get(key_handle).unwrap().clone();
+        let entry = self.take(&key).expect("every key has an entry");
+        Some((key, entry))
+    }
+    /// Drops the least frequently used entry.
+    fn evict(&mut self) {
+        if let Some((_, entry)) = self.pop() {
+            if expired(&self.clock, entry.expires) {
+                self.stats.expirations += 1;
+            } else {
+                self.stats.evictions += 1;
+            }
+        }
+    }
+    /// Removes the entry for `key`, `None` if there was none or it had
+    /// expired.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.find(key) {
+            return None;
+        }
+        self.take(key).map(|entry| entry.value)
+    }
+    /// Removes the least frequently used entry that hasn't expired, of
+    /// those the least recently used.
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        while let Some((key, entry)) = self.pop() {
+            if !expired(&self.clock, entry.expires) {
+                return Some((key, entry.value));
+            }
+            self.stats.expirations += 1;
+        }
+        None
+    }
+    /// Changes the capacity, evicting the least frequently used entries
+    /// that no longer fit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity == 0`.
+    pub fn resize(&mut self, capacity: usize) {
+        assert!(capacity > 0, "the capacity has to be at least 1");
+        while self.entries.len() > capacity {
+            self.evict();
+        }
+        self.capacity = capacity;
+    }
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LfuCache;
+    use crate::{CacheStats, ManualClock};
+
+    /// The use counts of the buckets and their keys, from the first bucket.
+    fn buckets<C>(cache: &LfuCache<&'static str, i32, C>) -> Vec<(u64, Vec<&'static str>)> {
+        cache
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.uses, bucket.keys.iter().copied().collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_eviction_order() {
+        let mut cache = LfuCache::new(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        cache.get("a");
+        cache.get("a");
+        cache.get("b");
+        assert_eq!(
+            buckets(&cache),
+            vec![(1, vec!["c"]), (2, vec!["b"]), (3, vec!["a"])]
+        );
+        // `c` was used least
+        cache.put("d", 4);
+        assert!(!cache.contains("c"));
+        // `d` is new, `b` was used more often
+        cache.put("e", 5);
+        assert_eq!(cache.peek("d"), None);
+        assert_eq!(
+            buckets(&cache),
+            vec![(1, vec!["e"]), (2, vec!["b"]), (3, vec!["a"])]
+        );
+        // a tie goes to the least recently used
+        cache.get("e");
+        assert_eq!(buckets(&cache), vec![(2, vec!["e", "b"]), (3, vec!["a"])]);
+        assert_eq!(cache.pop_lfu(), Some(("b", 2)));
+        assert_eq!(cache.put("a", 10), Some(1));
+        assert_eq!(cache.uses("a"), Some(4));
+        assert_eq!(cache.pop_lfu(), Some(("e", 5)));
+        assert_eq!(cache.pop_lfu(), Some(("a", 10)));
+        assert_eq!(cache.pop_lfu(), None);
+        assert!(cache.buckets.is_empty());
+        assert_eq!(cache.stats().evictions, 2);
+    }
+
+    #[test]
+    fn test_get_mut_remove_resize() {
+        let mut cache = LfuCache::new(4);
+        for (uses, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            cache.put(key, uses as i32);
+            for _ in 0..uses {
+                cache.get(key);
+            }
+        }
+        *cache.get_mut("a").unwrap() += 10;
+        assert_eq!(cache.uses("a"), Some(2));
+        assert_eq!(cache.remove("b"), Some(1));
+        assert_eq!(cache.remove("b"), None);
+        assert_eq!(
+            buckets(&cache),
+            vec![(2, vec!["a"]), (3, vec!["c"]), (4, vec!["d"])]
+        );
+        cache.resize(1);
+        assert_eq!(buckets(&cache), vec![(4, vec!["d"])]);
+        cache.resize(2);
+        cache.put("e", 5);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.capacity(), 2);
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(cache.buckets.is_empty());
+    }
+
+    #[test]
+    fn test_ttl_and_stats() {
+        let clock = ManualClock::new();
+        let mut cache = LfuCache::with_clock(2, clock.clone());
+        cache.put_with_ttl("a", 1, Duration::from_secs(5));
+        cache.put("b", 2);
+        assert_eq!(cache.get("a"), Some(&1));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.peek("a"), None);
+        assert_eq!(cache.uses("a"), None);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(buckets(&cache), vec![(1, vec!["b"])]);
+        // popping skips expired entries, however often they were used
+        cache.put_with_ttl("c", 3, Duration::from_secs(1));
+        for _ in 0..3 {
+            cache.get("c");
+        }
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.pop_lfu(), Some(("b", 2)));
+        assert_eq!(cache.pop_lfu(), None);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 4,
+                misses: 1,
+                evictions: 0,
+                expirations: 2,
+            }
+        );
+        assert_eq!(cache.stats().hit_rate(), 0.8);
+    }
+}
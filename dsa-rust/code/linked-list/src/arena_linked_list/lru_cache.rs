@@ -0,0 +1,492 @@
+//! A least recently used cache: a `HashMap` finds the entry of a key, an
+//! arena list keeps the entries in the order they were used, the most
+//! recent at the front.
+//!
+//! Both halves are O(1): the map stores the [`Handle`] of each entry, so a
+//! hit moves its entry to the front without walking the list, and a full
+//! cache drops the entry at the back.
+//!
+//! An entry can be given a time to live. Expired entries are not looked
+//! for actively, they are dropped once a lookup or an eviction runs into
+//! them, or by [`LruCache::purge_expired`].
+
+use std::{
+    borrow::Borrow,
+    cell::Cell,
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use super::{Handle, LinkedList};
+
+/// Where a cache gets the time from, so tests can move it by hand.
+pub trait Clock {
+    /// The time since some fixed start, it must never go backwards.
+    fn now(&self) -> Duration;
+}
+
+/// The time since the clock was made, by [`Instant`].
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so
+/// one can go into the cache and the other stay with the caller.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// What a cache has counted since it was made or its stats were reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups that found a live entry.
+    pub hits: u64,
+    /// Lookups that found nothing, or an entry that had expired.
+    pub misses: u64,
+    /// Live entries dropped to make room.
+    pub evictions: u64,
+    /// Expired entries dropped.
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    /// The share of lookups that were hits, 0 before the first lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Whether an entry that lives until `expires` is gone by now.
+pub(super) fn expired(clock: &impl Clock, expires: Option<Duration>) -> bool {
+    expires.is_some_and(|expires| expires <= clock.now())
+}
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    expires: Option<Duration>,
+}
+
+pub struct LruCache<K, V, C = SystemClock> {
+    /// Most recently used at the front.
+    entries: LinkedList<Entry<K, V>>,
+    handles: HashMap<K, Handle>,
+    capacity: usize,
+    clock: C,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// An empty cache for up to `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity == 0`.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_clock(capacity, SystemClock::default())
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, C: Clock> LruCache<K, V, C> {
+    /// An empty cache for up to `capacity` entries whose time to live is
+    /// measured by `clock`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity == 0`.
+    pub fn with_clock(capacity: usize, clock: C) -> Self {
+        assert!(capacity > 0, "the capacity has to be at least 1");
+        Self {
+            entries: LinkedList::with_capacity(capacity),
+            handles: HashMap::with_capacity(capacity),
+            capacity,
+            clock,
+            stats: CacheStats::default(),
+        }
+    }
+    /// The number of entries, counting expired ones that weren't dropped yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+    fn entry(&self, handle: Handle) -> &Entry<K, V> {
+        self.entries
+            .get(handle)
+            .expect("the map only holds live handles")
+    }
+    fn entry_mut(&mut self, handle: Handle) -> &mut Entry<K, V> {
+        self.entries
+            .get_mut(handle)
+            .expect("the map only holds live handles")
+    }
+    /// Takes the entry of `handle` out of both the list and the map.
+    fn take(&mut self, handle: Handle) -> Entry<K, V> {
+        let entry = self
+            .entries
+            .remove_handle(handle)
+            .expect("the map only holds live handles");
+        self.handles.remove(&entry.key);
+        entry
+    }
+    /// The handle of the live entry for `key`. An expired entry is dropped
+    /// on the way.
+    fn find<Q>(&mut self, key: &Q) -> Option<Handle>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = *self.handles.get(key)?;
+        if expired(&self.clock, self.entry(handle).expires) {
+            self.take(handle);
+            self.stats.expirations += 1;
+            return None;
+        }
+        Some(handle)
+    }
+    /// Like [`find`](Self::find), but counts a hit or a miss and makes the
+    /// entry the most recently used one.
+    fn lookup<Q>(&mut self, key: &Q) -> Option<Handle>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.find(key) {
+            Some(handle) => {
+                self.stats.hits += 1;
+                self.entries.move_to_front(handle);
+                Some(handle)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+    /// The value for `key`, which becomes the most recently used entry.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.lookup(key)?;
+        Some(&self.entry(handle).value)
+    }
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.lookup(key)?;
+        Some(&mut self.entry_mut(handle).value)
+    }
+    /// The value for `key` without using it: the order and the stats stay
+    /// as they are.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entry(*self.handles.get(key)?);
+        (!expired(&self.clock, entry.expires)).then_some(&entry.value)
+    }
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.peek(key).is_some()
+    }
+    /// Stores `value` for `key` until it is evicted, returning the value
+    /// it replaced. A new key in a full cache evicts the least recently
+    /// used entry.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, None)
+    }
+    /// Like [`put`](Self::put), but the entry expires after `ttl`.
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let expires = self.clock.now() + ttl;
+        self.insert(key, value, Some(expires))
+    }
+    fn insert(&mut self, key: K, value: V, expires: Option<Duration>) -> Option<V> {
+        if let Some(handle) = self.find(&key) {
+            self.entries.move_to_front(handle);
+            let entry = self.entry_mut(handle);
+            entry.expires = expires;
+            return Some(std::mem::replace(&mut entry.value, value));
+        }
+        if self.entries.len() == self.capacity {
+            self.evict();
+        }
+        let handle = self.entries.push_front(Entry {
+            key: key.clone(),
+            value,
+            expires,
+        });
+        self.handles.insert(key, handle);
+        None
+    }
+    /// Drops the least recently used entry.
+    fn evict(&mut self) {
+        if let Some(handle) = self.entries.back() {
+            let entry = self.take(handle);
+            if expired(&self.clock, entry.expires) {
+                self.stats.expirations += 1;
+            } else {
+                self.stats.evictions += 1;
+            }
+        }
+    }
+    /// Removes the entry for `key`, `None` if there was none or it had
+    /// expired.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.find(key)?;
+        Some(self.take(handle).value)
+    }
+    /// Removes the least recently used entry that hasn't expired.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        while let Some(handle) = self.entries.back() {
+            let entry = self.take(handle);
+            if !expired(&self.clock, entry.expires) {
+                return Some((entry.key, entry.value));
+            }
+            self.stats.expirations += 1;
+        }
+        None
+    }
+    /// Changes the capacity, evicting the least recently used entries that
+    /// no longer fit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity == 0`.
+    pub fn resize(&mut self, capacity: usize) {
+        assert!(capacity > 0, "the capacity has to be at least 1");
+        while self.entries.len() > capacity {
+            self.evict();
+        }
+        self.capacity = capacity;
+    }
+    /// Drops every expired entry and returns how many there were. O(n).
+    pub fn purge_expired(&mut self) -> usize {
+        let mut purged = 0;
+        let mut current = self.entries.front();
+        while let Some(handle) = current {
+            current = self.entries.next(handle);
+            if expired(&self.clock, self.entry(handle).expires) {
+                self.take(handle);
+                purged += 1;
+            }
+        }
+        self.stats.expirations += purged as u64;
+        purged
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.handles.clear();
+    }
+    /// The live entries, the most recently used first.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries
+            .iter()
+            .filter(|entry| !expired(&self.clock, entry.expires))
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, C> fmt::Debug for LruCache<K, V, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|entry| (&entry.key, &entry.value)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{CacheStats, LruCache, ManualClock};
+
+    fn keys(cache: &LruCache<&'static str, i32, ManualClock>) -> Vec<&'static str> {
+        cache.iter().map(|(key, _)| *key).collect()
+    }
+
+    fn cache(capacity: usize) -> (LruCache<&'static str, i32, ManualClock>, ManualClock) {
+        let clock = ManualClock::new();
+        (LruCache::with_clock(capacity, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_eviction_order() {
+        let (mut cache, _) = cache(2);
+        assert_eq!(cache.put("a", 1), None);
+        assert_eq!(cache.put("b", 2), None);
+        // using `a` leaves `b` as the least recently used
+        assert_eq!(cache.get("a"), Some(&1));
+        cache.put("c", 3);
+        assert_eq!(keys(&cache), vec!["c", "a"]);
+        assert_eq!(cache.get("b"), None);
+        // peeking doesn't count as a use
+        assert_eq!(cache.peek("a"), Some(&1));
+        cache.put("d", 4);
+        assert_eq!(keys(&cache), vec!["d", "c"]);
+        // replacing a value is a use
+        assert_eq!(cache.put("c", 30), Some(3));
+        assert_eq!(cache.pop_lru(), Some(("d", 4)));
+        assert_eq!(cache.pop_lru(), Some(("c", 30)));
+        assert_eq!(cache.pop_lru(), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_get_mut_remove_resize() {
+        let (mut cache, _) = cache(4);
+        for (key, value) in [("a", 1), ("b", 2), ("c", 3), ("d", 4)] {
+            cache.put(key, value);
+        }
+        *cache.get_mut("b").unwrap() += 10;
+        assert_eq!(cache.remove("c"), Some(3));
+        assert_eq!(cache.remove("c"), None);
+        assert_eq!(keys(&cache), vec!["b", "d", "a"]);
+        cache.resize(1);
+        assert_eq!(keys(&cache), vec!["b"]);
+        assert_eq!(cache.capacity(), 1);
+        assert_eq!(cache.stats().evictions, 2);
+        cache.resize(3);
+        cache.put("e", 5);
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(!cache.contains("b"));
+        // evicted slots are reused, the arena never holds more than the
+        // four entries it once had
+        for i in 0..100 {
+            cache.put(["x", "y", "z", "w"][i % 4], i as i32);
+        }
+        assert_eq!(cache.entries.slots(), 4);
+    }
+
+    #[test]
+    fn test_ttl() {
+        let (mut cache, clock) = cache(3);
+        cache.put_with_ttl("short", 1, Duration::from_secs(5));
+        cache.put_with_ttl("long", 2, Duration::from_secs(60));
+        cache.put("forever", 3);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.peek("short"), None);
+        assert_eq!(keys(&cache), vec!["forever", "long"]);
+        // still stored until something runs into it
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get("short"), None);
+        assert_eq!(cache.len(), 2);
+        // putting again renews or removes the time to live
+        cache.put("long", 20);
+        clock.advance(Duration::from_secs(100));
+        assert_eq!(cache.get("long"), Some(&20));
+        cache.put_with_ttl("a", 4, Duration::from_secs(1));
+        cache.put_with_ttl("b", 5, Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.purge_expired(), 2);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 1,
+                expirations: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_expired_entries_go_first() {
+        let (mut cache, clock) = cache(2);
+        cache.put_with_ttl("a", 1, Duration::from_secs(1));
+        cache.put("b", 2);
+        clock.advance(Duration::from_secs(2));
+        // `a` is gone, so popping skips it
+        assert_eq!(cache.pop_lru(), Some(("b", 2)));
+        assert_eq!(cache.stats().expirations, 1);
+        // an expired value isn't handed back when it is replaced
+        cache.put_with_ttl("c", 3, Duration::from_secs(1));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.put("c", 4), None);
+        assert_eq!(cache.remove("c"), Some(4));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut cache = LruCache::new(2);
+        cache.put(String::from("a"), 1);
+        assert_eq!(cache.stats().hit_rate(), 0.0);
+        cache.get("a");
+        cache.get("a");
+        cache.get("a");
+        cache.get("b");
+        assert_eq!(cache.stats().hits, 3);
+        assert_eq!(cache.stats().misses, 1);
+        assert_eq!(cache.stats().hit_rate(), 0.75);
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats::default());
+        assert_eq!(format!("{:?}", cache), "{\"a\": 1}");
+    }
+
+    #[test]
+    #[should_panic(expected = "the capacity has to be at least 1")]
+    fn test_zero_capacity() {
+        LruCache::<i32, i32>::new(0);
+    }
+}
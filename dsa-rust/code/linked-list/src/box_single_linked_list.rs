@@ -1,10 +1,18 @@
+mod algorithms;
+mod polynomial;
+mod sort;
+
 use std::fmt;
 
-use crate::LinkedListT;
+use crate::{DropObserver, Layout, LinkedListT, Location, NodeLayout, Pointer, PointerKind, Root};
+
+pub use polynomial::Polynomial;
+
+type Link<T> = Option<Box<Node<T>>>;
 
 struct Node<T: fmt::Display + PartialEq> {
     value: T,
-    next: Option<Box<Node<T>>>,
+    next: Link<T>,
 }
 
 impl<T: fmt::Display + PartialEq> Node<T> {
@@ -13,20 +21,201 @@ impl<T: fmt::Display + PartialEq> Node<T> {
     }
 }
 
-impl<T: fmt::Display + PartialEq> Drop for Node<T> {
+pub struct LinkedList<T: fmt::Display + PartialEq> {
+    head: Link<T>,
+    len: usize,
+    drop_observer: Option<DropObserver<T>>,
+}
+
+impl<T: fmt::Display + PartialEq> LinkedList<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_deref_mut(),
+            len: self.len,
+        }
+    }
+    /// The link pointing at the node at `index` (the final `None` for
+    /// `index == len`).
+    fn link_at(&mut self, index: usize) -> &mut Link<T> {
+        let mut current = &mut self.head;
+        for _ in 0..index {
+            current = &mut current.as_mut().unwrap().next;
+        }
+        current
+    }
+}
+
+// The default drop of `head` would drop each node from inside the drop of
+// the node before it, which overflows the stack on long lists.
+impl<T: fmt::Display + PartialEq> Drop for LinkedList<T> {
     fn drop(&mut self) {
-        println!("Node with value {} has been dropped.", self.value);
+        self.clear();
     }
 }
 
-pub struct LinkedList<T: fmt::Display + PartialEq> {
-    head: Option<Box<Node<T>>>,
+impl<T: fmt::Display + PartialEq> fmt::Display for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for value in self {
+            write!(f, "{} -> ", value)?;
+        }
+        write!(f, "None")
+    }
+}
+
+impl<T: fmt::Display + PartialEq + fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: fmt::Display + PartialEq + Clone> Clone for LinkedList<T> {
+    /// Copies the elements only, the drop observer stays with the original.
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Display + PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: fmt::Display + PartialEq> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: fmt::Display + PartialEq> Extend<T> for LinkedList<T> {
+    /// Walks to the end once and appends everything from there, instead of
+    /// paying a full walk for each `push_back`.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut added = 0;
+        let mut link = self.link_at(self.len);
+        for value in iter {
+            let node = link.insert(Box::new(Node::new(value)));
+            link = &mut node.next;
+            added += 1;
+        }
+        self.len += added;
+    }
+}
+
+pub struct Iter<'a, T: fmt::Display + PartialEq> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<'a, T: fmt::Display + PartialEq> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        self.len -= 1;
+        Some(&node.value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: fmt::Display + PartialEq> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T: fmt::Display + PartialEq> {
+    next: Option<&'a mut Node<T>>,
+    len: usize,
+}
+
+impl<'a, T: fmt::Display + PartialEq> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+        self.next = node.next.as_deref_mut();
+        self.len -= 1;
+        Some(&mut node.value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: fmt::Display + PartialEq> ExactSizeIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T: fmt::Display + PartialEq> {
+    list: LinkedList<T>,
+}
+
+impl<T: fmt::Display + PartialEq> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T: fmt::Display + PartialEq> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: fmt::Display + PartialEq> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T: fmt::Display + PartialEq> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T: fmt::Display + PartialEq> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
 }
 
 impl<T: fmt::Display + PartialEq> LinkedListT for LinkedList<T> {
     type Item = T;
     fn new() -> Self {
-        Self { head: None }
+        Self {
+            head: None,
+            len: 0,
+            drop_observer: None,
+        }
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn contains(&self, value: &Self::Item) -> bool {
+        self.iter().any(|v| v == value)
+    }
+    fn get(&self, index: usize) -> Option<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.iter().nth(index).cloned()
+    }
+    fn to_vec(&self) -> Vec<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.iter().cloned().collect()
     }
     fn push_front(&mut self, value: Self::Item) {
         let new_node = Box::new(Node {
@@ -34,54 +223,146 @@ impl<T: fmt::Display + PartialEq> LinkedListT for LinkedList<T> {
             next: self.head.take(),
         });
         self.head = Some(new_node);
+        self.len += 1;
     }
     fn push_back(&mut self, value: Self::Item) {
         let new_node = Box::new(Node::new(value));
-
-        if self.head.is_none() {
-            self.head = Some(new_node);
-            return;
-        }
-
-        let mut current = &mut self.head;
-        while let Some(ref mut node) = current {
-            current = &mut node.next;
+        *self.link_at(self.len) = Some(new_node);
+        self.len += 1;
+    }
+    fn pop_front(&mut self) -> Option<Self::Item> {
+        self.remove_at(0)
+    }
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        self.remove_at(self.len.checked_sub(1)?)
+    }
+    fn insert_at(&mut self, index: usize, value: Self::Item) {
+        assert!(index <= self.len, "index {} out of bounds", index);
+        let link = self.link_at(index);
+        let new_node = Box::new(Node {
+            value,
+            next: link.take(),
+        });
+        *link = Some(new_node);
+        self.len += 1;
+    }
+    fn remove_at(&mut self, index: usize) -> Option<Self::Item> {
+        if index >= self.len {
+            return None;
         }
-
-        *current = Some(new_node);
+        let link = self.link_at(index);
+        let mut node = link.take().unwrap();
+        *link = node.next.take();
+        self.len -= 1;
+        Some(node.value)
     }
-    fn remove(&mut self, value: Self::Item) {
+    fn remove(&mut self, value: &Self::Item) -> bool {
         let mut current = &mut self.head;
-        while let Some(_) = current {
-            if current.as_ref().unwrap().value == value {
-                let next = current.as_mut().unwrap().next.take();
-                *current = next;
-                return;
+        while current.is_some() {
+            if current.as_ref().unwrap().value == *value {
+                let mut node = current.take().unwrap();
+                *current = node.next.take();
+                self.len -= 1;
+                if let Some(observer) = &mut self.drop_observer {
+                    observer(&node.value);
+                }
+                return true;
             } else {
                 current = &mut current.as_mut().unwrap().next;
             }
         }
+        false
     }
-    fn update(&mut self, old_value: Self::Item, new_value: Self::Item) {
-        let mut current = &mut self.head;
-        while let Some(node) = current {
-            if node.value == old_value {
-                node.value = new_value;
-                return;
+    fn update(&mut self, old_value: &Self::Item, new_value: Self::Item) -> Option<Self::Item> {
+        let value = self.iter_mut().find(|v| **v == *old_value)?;
+        Some(std::mem::replace(value, new_value))
+    }
+    fn clear(&mut self) {
+        let mut current = self.head.take();
+        while let Some(mut node) = current {
+            current = node.next.take();
+            if let Some(observer) = &mut self.drop_observer {
+                observer(&node.value);
             }
-            current = &mut node.next;
         }
+        self.len = 0;
+    }
+    fn sort(&mut self)
+    where
+        T: PartialOrd,
+    {
+        self.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
     }
-    fn print(&self) {
-        let mut current = &self.head;
+    fn layout(&self) -> Layout {
+        let location = |node: &Node<T>| Location::Address(node as *const Node<T> as usize);
+        let link = |link: &Link<T>| {
+            link.as_deref()
+                .map(|node| Pointer::new(PointerKind::Box, location(node)))
+        };
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut current = self.head.as_deref();
         while let Some(node) = current {
-            print!("{} -> ", node.value);
-            current = &node.next;
+            let mut layout = NodeLayout::new(location(node), &node.value);
+            layout.next = link(&node.next);
+            nodes.push(layout);
+            current = node.next.as_deref();
+        }
+        Layout {
+            roots: vec![Root {
+                name: "head",
+                link: link(&self.head),
+            }],
+            nodes,
         }
-        println!("None");
     }
     #[inline(always)]
     fn header(&self) -> &str {
         "--- Single Linked List (Box) CLI ---"
     }
+    fn set_drop_observer(&mut self, observer: DropObserver<Self::Item>) {
+        self.drop_observer = Some(observer);
+    }
+}
+
+#[cfg(test)]
+impl<T: fmt::Display + PartialEq + Clone> crate::tests::Snapshot for LinkedList<T> {
+    fn snapshot(&self) -> Vec<T> {
+        self.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+    use crate::LinkedListT;
+
+    #[test]
+    fn test_iter() {
+        let mut list: LinkedList<i32> = (1..=4).collect();
+        assert_eq!(list.iter().len(), 4);
+        for value in list.iter_mut() {
+            *value *= 10;
+        }
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![10, 20, 30, 40]
+        );
+        assert_eq!(list.into_iter().last(), Some(40));
+    }
+
+    #[test]
+    fn test_extend_clone_eq() {
+        let mut list: LinkedList<i32> = LinkedList::new();
+        list.extend([1, 2]);
+        list.push_front(0);
+        list.extend(vec![3]);
+        assert_eq!(format!("{:?}", list), "[0, 1, 2, 3]");
+        assert_eq!(list.len(), 4);
+        let mut copy = list.clone();
+        assert_eq!(copy, list);
+        copy.pop_back();
+        assert_ne!(copy, list);
+        copy.push_back(4);
+        assert_ne!(copy, list);
+    }
 }
@@ -0,0 +1,376 @@
+//! The classic interview problems on a singly linked list.
+//!
+//! All of them relink the existing nodes instead of allocating new ones.
+//! `n` is the length of the list.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::sort::merge;
+use super::{Link, LinkedList};
+use crate::LinkedListT;
+
+/// Reverses a detached chain of nodes and returns its new head.
+fn reverse_chain<T: fmt::Display + PartialEq>(mut current: Link<T>) -> Link<T> {
+    let mut reversed = None;
+    while let Some(mut node) = current {
+        current = node.next.take();
+        node.next = reversed;
+        reversed = Some(node);
+    }
+    reversed
+}
+
+impl<T: fmt::Display + PartialEq> LinkedList<T> {
+    /// Reverses the list in place. O(n) time, O(1) space.
+    pub fn reverse(&mut self) {
+        self.head = reverse_chain(self.head.take());
+    }
+
+    /// Reverses every run of `k` nodes, a shorter run left at the end
+    /// keeps its order: `1 2 3 4 5` with `k = 2` becomes `2 1 4 3 5`.
+    /// O(n) time, O(1) space.
+    pub fn reverse_groups(&mut self, k: usize) {
+        if k < 2 {
+            return;
+        }
+        let mut rest = self.head.take();
+        let mut tail = &mut self.head;
+        loop {
+            // look ahead first, an incomplete group stays as it is
+            let mut probe = rest.as_deref();
+            for _ in 0..k {
+                probe = match probe {
+                    Some(node) => node.next.as_deref(),
+                    None => {
+                        *tail = rest;
+                        return;
+                    }
+                };
+            }
+            let mut group = None;
+            for _ in 0..k {
+                let mut node = rest.take().unwrap();
+                rest = node.next.take();
+                node.next = group;
+                group = Some(node);
+            }
+            *tail = group;
+            for _ in 0..k {
+                tail = &mut tail.as_mut().unwrap().next;
+            }
+        }
+    }
+
+    /// The middle element, the second of the two middles for an even
+    /// length. The fast pointer moves two nodes per step, so the slow one
+    /// is halfway when it reaches the end. O(n) time, O(1) space.
+    pub fn middle(&self) -> Option<&T> {
+        let mut slow = self.head.as_deref()?;
+        let mut fast = self.head.as_deref();
+        while let Some(next) = fast.and_then(|node| node.next.as_deref()) {
+            slow = slow.next.as_deref().unwrap();
+            fast = next.next.as_deref();
+        }
+        Some(&slow.value)
+    }
+
+    /// Removes the `n`th node counted from the end, `n = 1` being the last.
+    ///
+    /// The textbook version runs a lead pointer `n` nodes ahead so it works
+    /// without knowing the length. This list keeps its length, and `Box`
+    /// links couldn't be read through one pointer while another one edits
+    /// them anyway, so it is a plain walk. O(n) time, O(1) space.
+    pub fn remove_nth_from_end(&mut self, n: usize) -> Option<T> {
+        if n == 0 || n > self.len {
+            return None;
+        }
+        let link = self.link_at(self.len - n);
+        let mut node = link.take().unwrap();
+        *link = node.next.take();
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    /// Merges two sorted lists into one sorted list, taking from `self`
+    /// first on ties so equal elements keep their order.
+    /// O(n + m) time, O(1) space.
+    pub fn merge_sorted(mut self, mut other: Self) -> Self
+    where
+        T: PartialOrd,
+    {
+        let mut merged = Self::new();
+        merged.len = self.len + other.len;
+        merged.drop_observer = self.drop_observer.take();
+        merged.head = merge(self.head.take(), other.head.take(), &mut |a, b| {
+            a.partial_cmp(b).unwrap_or(Ordering::Equal)
+        });
+        merged
+    }
+
+    /// Whether the list reads the same both ways.
+    ///
+    /// Reverses the second half, compares it against the first and
+    /// reverses it back, which is why it needs `&mut self`.
+    /// O(n) time, O(1) space.
+    pub fn is_palindrome(&mut self) -> bool {
+        // the middle element of an odd length stays with the first half
+        let second_start = self.len - self.len / 2;
+        let second = reverse_chain(self.link_at(second_start).take());
+        let mut a = self.head.as_deref();
+        let mut b = second.as_deref();
+        let mut same = true;
+        while let (Some(x), Some(y)) = (a, b) {
+            if x.value != y.value {
+                same = false;
+                break;
+            }
+            a = x.next.as_deref();
+            b = y.next.as_deref();
+        }
+        *self.link_at(second_start) = reverse_chain(second);
+        same
+    }
+
+    /// Rotates the list `k` places to the right: `1 2 3 4 5` with `k = 2`
+    /// becomes `4 5 1 2 3`. O(n) time, O(1) space.
+    pub fn rotate(&mut self, k: usize) {
+        let k = match self.len {
+            0 => return,
+            len => k % len,
+        };
+        if k == 0 {
+            return;
+        }
+        let moved = self.link_at(self.len - k).take();
+        let front = std::mem::replace(&mut self.head, moved);
+        *self.link_at(k) = front;
+    }
+
+    /// Moves every element less than `pivot` in front of the others,
+    /// keeping the order within both groups. O(n) time, O(1) space.
+    pub fn partition(&mut self, pivot: &T)
+    where
+        T: PartialOrd,
+    {
+        let mut less = None;
+        let mut rest = None;
+        let mut less_tail = &mut less;
+        let mut rest_tail = &mut rest;
+        let mut current = self.head.take();
+        while let Some(mut node) = current {
+            current = node.next.take();
+            if node.value < *pivot {
+                less_tail = &mut less_tail.insert(node).next;
+            } else {
+                rest_tail = &mut rest_tail.insert(node).next;
+            }
+        }
+        *less_tail = rest;
+        self.head = less;
+    }
+
+    /// Removes repeated elements from a sorted list, where they sit next to
+    /// each other. O(n) time, O(1) space.
+    pub fn dedup_sorted(&mut self) {
+        let mut current = self.head.as_deref_mut();
+        while let Some(node) = current {
+            while node
+                .next
+                .as_ref()
+                .is_some_and(|next| next.value == node.value)
+            {
+                let mut duplicate = node.next.take().unwrap();
+                node.next = duplicate.next.take();
+                self.len -= 1;
+                if let Some(observer) = &mut self.drop_observer {
+                    observer(&duplicate.value);
+                }
+            }
+            current = node.next.as_deref_mut();
+        }
+    }
+
+    /// Removes repeated elements from an unsorted list, keeping the first
+    /// of each. Every node scans the rest of the list for copies of itself,
+    /// O(n²) time but O(1) space and only `PartialEq` needed; a `HashSet`
+    /// of seen values gets it down to O(n) time for O(n) space.
+    pub fn dedup_unsorted(&mut self) {
+        let mut current = self.head.as_deref_mut();
+        while let Some(node) = current {
+            let mut link = &mut node.next;
+            while link.is_some() {
+                if link.as_ref().unwrap().value == node.value {
+                    let mut duplicate = link.take().unwrap();
+                    *link = duplicate.next.take();
+                    self.len -= 1;
+                    if let Some(observer) = &mut self.drop_observer {
+                        observer(&duplicate.value);
+                    }
+                } else {
+                    link = &mut link.as_mut().unwrap().next;
+                }
+            }
+            current = node.next.as_deref_mut();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::LinkedList;
+    use crate::LinkedListT;
+
+    fn list(values: &[i32]) -> LinkedList<i32> {
+        values.iter().copied().collect()
+    }
+
+    fn check(list: &LinkedList<i32>, expected: &[i32]) {
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+        assert_eq!(list.len(), expected.len());
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut l = list(&[1, 2, 3]);
+        l.reverse();
+        check(&l, &[3, 2, 1]);
+        let mut l = list(&[]);
+        l.reverse();
+        check(&l, &[]);
+    }
+
+    #[test]
+    fn test_reverse_groups() {
+        let mut l = list(&[1, 2, 3, 4, 5]);
+        l.reverse_groups(2);
+        check(&l, &[2, 1, 4, 3, 5]);
+        let mut l = list(&[1, 2, 3, 4, 5, 6]);
+        l.reverse_groups(3);
+        check(&l, &[3, 2, 1, 6, 5, 4]);
+        l.reverse_groups(7);
+        check(&l, &[3, 2, 1, 6, 5, 4]);
+        l.reverse_groups(1);
+        check(&l, &[3, 2, 1, 6, 5, 4]);
+    }
+
+    #[test]
+    fn test_middle() {
+        assert_eq!(list(&[]).middle(), None);
+        assert_eq!(list(&[1]).middle(), Some(&1));
+        assert_eq!(list(&[1, 2, 3]).middle(), Some(&2));
+        assert_eq!(list(&[1, 2, 3, 4]).middle(), Some(&3));
+    }
+
+    #[test]
+    fn test_remove_nth_from_end() {
+        let mut l = list(&[1, 2, 3, 4]);
+        assert_eq!(l.remove_nth_from_end(0), None);
+        assert_eq!(l.remove_nth_from_end(5), None);
+        assert_eq!(l.remove_nth_from_end(1), Some(4));
+        assert_eq!(l.remove_nth_from_end(3), Some(1));
+        check(&l, &[2, 3]);
+    }
+
+    #[test]
+    fn test_merge_sorted() {
+        let merged = list(&[1, 3, 5, 7]).merge_sorted(list(&[2, 3, 4]));
+        check(&merged, &[1, 2, 3, 3, 4, 5, 7]);
+        check(&list(&[]).merge_sorted(list(&[1])), &[1]);
+        check(&list(&[1]).merge_sorted(list(&[])), &[1]);
+    }
+
+    /// Compares on the number only, the letter tells equal keys apart.
+    struct Keyed(i32, char);
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            self.0.partial_cmp(&other.0)
+        }
+    }
+
+    impl std::fmt::Display for Keyed {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}{}", self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn test_merge_sorted_is_stable() {
+        let a: LinkedList<Keyed> = [Keyed(1, 'a'), Keyed(2, 'a')].into_iter().collect();
+        let b: LinkedList<Keyed> = [Keyed(1, 'b'), Keyed(2, 'b')].into_iter().collect();
+        assert_eq!(
+            a.merge_sorted(b).to_string(),
+            "1a -> 1b -> 2a -> 2b -> None"
+        );
+    }
+
+    #[test]
+    fn test_is_palindrome() {
+        for (values, expected) in [
+            (&[][..], true),
+            (&[1][..], true),
+            (&[1, 2, 1][..], true),
+            (&[1, 2, 2, 1][..], true),
+            (&[1, 2][..], false),
+            (&[1, 2, 3, 1][..], false),
+        ] {
+            let mut l = list(values);
+            assert_eq!(l.is_palindrome(), expected, "{:?}", values);
+            // the list has to be put back the way it was
+            check(&l, values);
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut l = list(&[1, 2, 3, 4, 5]);
+        l.rotate(2);
+        check(&l, &[4, 5, 1, 2, 3]);
+        l.rotate(5);
+        check(&l, &[4, 5, 1, 2, 3]);
+        l.rotate(8);
+        check(&l, &[1, 2, 3, 4, 5]);
+        let mut l = list(&[]);
+        l.rotate(3);
+        check(&l, &[]);
+    }
+
+    #[test]
+    fn test_partition() {
+        let mut l = list(&[3, 5, 8, 5, 10, 2, 1]);
+        l.partition(&5);
+        check(&l, &[3, 2, 1, 5, 8, 5, 10]);
+        l.partition(&0);
+        check(&l, &[3, 2, 1, 5, 8, 5, 10]);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut l = list(&[1, 1, 2, 3, 3, 3]);
+        l.dedup_sorted();
+        check(&l, &[1, 2, 3]);
+        let mut l = list(&[3, 1, 3, 2, 1, 3]);
+        l.dedup_unsorted();
+        check(&l, &[3, 1, 2]);
+        // removed duplicates are reported like any other dropped node
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let count = Arc::clone(&dropped);
+        let mut l = list(&[2, 2, 2]);
+        l.set_drop_observer(Box::new(move |_| {
+            count.fetch_add(1, Ordering::Relaxed);
+        }));
+        l.dedup_sorted();
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+        check(&l, &[2]);
+    }
+}
@@ -0,0 +1,289 @@
+//! Sparse polynomials with integer coefficients, kept as a list of terms.
+//!
+//! Only terms with a coefficient other than 0 are stored, in descending
+//! order of their exponent, so `3x^1000 + 1` takes two nodes. Keeping the
+//! order lets two polynomials be added in one pass over both lists, like
+//! merging two sorted lists.
+//!
+//! Coefficients are `i64` and overflow like `i64` arithmetic does.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter;
+use std::ops::{Add, Mul};
+
+use super::LinkedList;
+use crate::LinkedListT;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Term {
+    coefficient: i64,
+    exponent: u32,
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x^{}", self.coefficient, self.exponent)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    /// Descending by exponent, no coefficient is 0.
+    terms: LinkedList<Term>,
+}
+
+impl Polynomial {
+    /// The zero polynomial, which has no terms.
+    pub fn new() -> Self {
+        Self {
+            terms: LinkedList::new(),
+        }
+    }
+    /// `coefficient·x^exponent`.
+    pub fn monomial(coefficient: i64, exponent: u32) -> Self {
+        let mut polynomial = Self::new();
+        if coefficient != 0 {
+            polynomial.terms.push_front(Term {
+                coefficient,
+                exponent,
+            });
+        }
+        polynomial
+    }
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+    /// The highest exponent, `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<u32> {
+        self.terms.iter().next().map(|term| term.exponent)
+    }
+    /// The `(coefficient, exponent)` pairs, the highest exponent first.
+    pub fn terms(&self) -> impl Iterator<Item = (i64, u32)> + '_ {
+        self.terms
+            .iter()
+            .map(|term| (term.coefficient, term.exponent))
+    }
+    /// The value at `x`, by Horner's rule with a power for each gap
+    /// between two exponents. O(t·log d) for `t` terms and degree `d`.
+    pub fn evaluate(&self, x: i64) -> i64 {
+        let mut value = 0;
+        let mut previous = self.degree().unwrap_or(0);
+        for term in &self.terms {
+            value = value * x.pow(previous - term.exponent) + term.coefficient;
+            previous = term.exponent;
+        }
+        value * x.pow(previous)
+    }
+    /// Multiplies `other` by each term of `self` and adds up the results.
+    /// Each of those products is already in order, so that is `t₁` merges.
+    fn product(&self, other: &Self) -> Self {
+        let mut product = Self::new();
+        for term in &self.terms {
+            let partial = other.terms.iter().map(|factor| Term {
+                coefficient: term.coefficient * factor.coefficient,
+                exponent: term.exponent + factor.exponent,
+            });
+            let partial = Self {
+                terms: partial.collect(),
+            };
+            product = &product + &partial;
+        }
+        product
+    }
+    pub fn derivative(&self) -> Self {
+        let terms = self
+            .terms
+            .iter()
+            .filter(|term| term.exponent > 0)
+            .map(|term| Term {
+                coefficient: term.coefficient * i64::from(term.exponent),
+                exponent: term.exponent - 1,
+            });
+        Self {
+            terms: terms.collect(),
+        }
+    }
+}
+
+impl Default for Polynomial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromIterator<(i64, u32)> for Polynomial {
+    /// Sums `(coefficient, exponent)` pairs in any order, like terms are
+    /// combined.
+    fn from_iter<I: IntoIterator<Item = (i64, u32)>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Self::new(), |sum, (coefficient, exponent)| {
+                &sum + &Self::monomial(coefficient, exponent)
+            })
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    /// Merges the two term lists, adding the coefficients of equal
+    /// exponents. O(t₁ + t₂).
+    fn add(self, other: &Polynomial) -> Polynomial {
+        let mut left = self.terms.iter().peekable();
+        let mut right = other.terms.iter().peekable();
+        let sum = iter::from_fn(|| loop {
+            // the side with the higher exponent goes first
+            let order = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => l.exponent.cmp(&r.exponent),
+                (Some(_), None) => Ordering::Greater,
+                (None, Some(_)) => Ordering::Less,
+                (None, None) => return None,
+            };
+            let term = match order {
+                Ordering::Greater => *left.next()?,
+                Ordering::Less => *right.next()?,
+                Ordering::Equal => {
+                    let (l, r) = (left.next()?, right.next()?);
+                    Term {
+                        coefficient: l.coefficient + r.coefficient,
+                        exponent: l.exponent,
+                    }
+                }
+            };
+            // terms that cancel out are left out
+            if term.coefficient != 0 {
+                return Some(term);
+            }
+        });
+        Polynomial {
+            terms: sum.collect(),
+        }
+    }
+}
+
+impl Add for Polynomial {
+    type Output = Polynomial;
+    fn add(self, other: Polynomial) -> Polynomial {
+        &self + &other
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+    fn mul(self, other: &Polynomial) -> Polynomial {
+        self.product(other)
+    }
+}
+
+impl Mul for Polynomial {
+    type Output = Polynomial;
+    fn mul(self, other: Polynomial) -> Polynomial {
+        &self * &other
+    }
+}
+
+impl fmt::Display for Polynomial {
+    /// Like it is written by hand: `3x^4 - x + 1`, `0` for no terms.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        for (index, term) in self.terms.iter().enumerate() {
+            let sign = match (index, term.coefficient < 0) {
+                (0, false) => "",
+                (0, true) => "-",
+                (_, false) => " + ",
+                (_, true) => " - ",
+            };
+            write!(f, "{}", sign)?;
+            let magnitude = term.coefficient.unsigned_abs();
+            if magnitude != 1 || term.exponent == 0 {
+                write!(f, "{}", magnitude)?;
+            }
+            match term.exponent {
+                0 => {}
+                1 => write!(f, "x")?,
+                exponent => write!(f, "x^{}", exponent)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Polynomial;
+
+    fn polynomial(terms: &[(i64, u32)]) -> Polynomial {
+        terms.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Polynomial::new().to_string(), "0");
+        assert_eq!(
+            polynomial(&[(1, 0), (-2, 1), (3, 4)]).to_string(),
+            "3x^4 - 2x + 1"
+        );
+        assert_eq!(polynomial(&[(-1, 2), (1, 1)]).to_string(), "-x^2 + x");
+        assert_eq!(polynomial(&[(-1, 0)]).to_string(), "-1");
+        assert_eq!(polynomial(&[(5, 1000), (0, 3)]).to_string(), "5x^1000");
+    }
+
+    #[test]
+    fn test_from_terms() {
+        let p = polynomial(&[(1, 2), (4, 0), (2, 2), (-4, 0)]);
+        assert_eq!(p.terms().collect::<Vec<_>>(), vec![(3, 2)]);
+        assert_eq!(p.degree(), Some(2));
+        assert_eq!(polynomial(&[(1, 3), (-1, 3)]), Polynomial::new());
+        assert_eq!(Polynomial::monomial(0, 7).degree(), None);
+    }
+
+    #[test]
+    fn test_add() {
+        let p = polynomial(&[(3, 4), (-2, 1), (1, 0)]);
+        let q = polynomial(&[(1, 5), (2, 1), (-1, 0)]);
+        assert_eq!((&p + &q).to_string(), "x^5 + 3x^4");
+        assert_eq!(&p + &Polynomial::new(), p);
+        assert_eq!((p.clone() + q.clone()).terms().count(), 2);
+    }
+
+    #[test]
+    fn test_mul() {
+        // (x + 1)(x - 1) = x² - 1
+        let p = polynomial(&[(1, 1), (1, 0)]);
+        let q = polynomial(&[(1, 1), (-1, 0)]);
+        assert_eq!((&p * &q).to_string(), "x^2 - 1");
+        // (x + 1)³
+        let cube = &(&p * &p) * &p;
+        assert_eq!(cube.to_string(), "x^3 + 3x^2 + 3x + 1");
+        assert!((p * Polynomial::new()).is_zero());
+        // sparse factors stay sparse
+        let sparse = polynomial(&[(2, 100), (1, 0)]) * polynomial(&[(3, 50)]);
+        assert_eq!(sparse.to_string(), "6x^150 + 3x^50");
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let p = polynomial(&[(3, 4), (-2, 1), (1, 0)]);
+        assert_eq!(p.evaluate(2), 3 * 16 - 4 + 1);
+        assert_eq!(p.evaluate(0), 1);
+        assert_eq!(polynomial(&[(1, 10)]).evaluate(2), 1024);
+        assert_eq!(polynomial(&[(1, 3), (1, 1)]).evaluate(-3), -27 - 3);
+        assert_eq!(Polynomial::new().evaluate(5), 0);
+        // a product evaluates to the product of the values
+        let q = polynomial(&[(1, 2), (-7, 0)]);
+        for x in -5..=5 {
+            assert_eq!((&p * &q).evaluate(x), p.evaluate(x) * q.evaluate(x));
+            assert_eq!((&p + &q).evaluate(x), p.evaluate(x) + q.evaluate(x));
+        }
+    }
+
+    #[test]
+    fn test_derivative() {
+        let p = polynomial(&[(3, 4), (-2, 1), (1, 0)]);
+        assert_eq!(p.derivative().to_string(), "12x^3 - 2");
+        assert_eq!(p.derivative().derivative().to_string(), "36x^2");
+        assert!(polynomial(&[(7, 0)]).derivative().is_zero());
+    }
+}
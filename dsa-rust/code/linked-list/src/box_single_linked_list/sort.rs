@@ -0,0 +1,198 @@
+//! Sorting by relinking the nodes, no value is moved or copied.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{Link, LinkedList};
+
+/// Merges two sorted chains, taking from `a` unless `b` is strictly less,
+/// which keeps equal elements in their original order.
+pub(super) fn merge<T, F>(mut a: Link<T>, mut b: Link<T>, compare: &mut F) -> Link<T>
+where
+    T: fmt::Display + PartialEq,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut merged = None;
+    let mut tail = &mut merged;
+    while let (Some(x), Some(y)) = (&a, &b) {
+        let from = if compare(&y.value, &x.value) == Ordering::Less {
+            &mut b
+        } else {
+            &mut a
+        };
+        let mut node = from.take().unwrap();
+        *from = node.next.take();
+        tail = &mut tail.insert(node).next;
+    }
+    *tail = a.or(b);
+    merged
+}
+
+/// Cuts `chain` after `n` nodes and returns what came after them.
+fn split_after<T: fmt::Display + PartialEq>(chain: &mut Link<T>, n: usize) -> Link<T> {
+    let mut link = chain;
+    for _ in 0..n {
+        match link {
+            Some(node) => link = &mut node.next,
+            None => return None,
+        }
+    }
+    link.take()
+}
+
+fn merge_sort<T, F>(mut head: Link<T>, len: usize, compare: &mut F) -> Link<T>
+where
+    T: fmt::Display + PartialEq,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if len < 2 {
+        return head;
+    }
+    let second = split_after(&mut head, len / 2);
+    let a = merge_sort(head, len / 2, compare);
+    let b = merge_sort(second, len - len / 2, compare);
+    merge(a, b, compare)
+}
+
+impl<T: fmt::Display + PartialEq> LinkedList<T> {
+    /// Stable merge sort. Splits the list in halves down to single nodes
+    /// and merges them back in order. O(n log n) time, O(log n) stack.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.head = merge_sort(self.head.take(), self.len, &mut compare);
+    }
+
+    /// Stable merge sort on the keys `f` extracts.
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Stable merge sort without recursion: merges neighbouring runs of 1,
+    /// then 2, 4, ... nodes until one run is left. O(n log n) time, O(1)
+    /// space.
+    pub fn sort_bottom_up_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut width = 1;
+        while width < self.len {
+            let mut rest = self.head.take();
+            let mut tail = &mut self.head;
+            while rest.is_some() {
+                let mut a = rest;
+                let mut b = split_after(&mut a, width);
+                rest = split_after(&mut b, width);
+                *tail = merge(a, b, &mut compare);
+                while let Some(node) = tail {
+                    tail = &mut node.next;
+                }
+            }
+            width *= 2;
+        }
+    }
+
+    /// Stable insertion sort: moves the nodes one by one into a sorted
+    /// chain, behind any equal ones. O(n²) time, but O(n) on a list that
+    /// is already sorted in reverse and fast on short lists.
+    pub fn insertion_sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut sorted: Link<T> = None;
+        let mut rest = self.head.take();
+        while let Some(mut node) = rest {
+            rest = node.next.take();
+            let mut link = &mut sorted;
+            while let Some(next) = link {
+                if compare(&next.value, &node.value) == Ordering::Greater {
+                    break;
+                }
+                link = &mut link.as_mut().unwrap().next;
+            }
+            node.next = link.take();
+            *link = Some(node);
+        }
+        self.head = sorted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use super::LinkedList;
+    use crate::LinkedListT;
+
+    /// `id` numbers the elements, so a reordering of equal keys shows up.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Item {
+        key: i32,
+        id: usize,
+    }
+
+    impl fmt::Display for Item {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}#{}", self.key, self.id)
+        }
+    }
+
+    type Sort = fn(&mut LinkedList<Item>);
+
+    const SORTS: [(&str, Sort); 3] = [
+        ("merge", |l| l.sort_by(|a, b| a.key.cmp(&b.key))),
+        ("bottom up", |l| {
+            l.sort_bottom_up_by(|a, b| a.key.cmp(&b.key))
+        }),
+        ("insertion", |l| {
+            l.insertion_sort_by(|a, b| a.key.cmp(&b.key))
+        }),
+    ];
+
+    /// Every sort against `slice::sort_by_key`, which is stable as well.
+    fn check(keys: &[i32]) {
+        let items: Vec<Item> = (keys.iter().zip(0..))
+            .map(|(&key, id)| Item { key, id })
+            .collect();
+        let mut expected = items.clone();
+        expected.sort_by_key(|item| item.key);
+        for (name, sort) in SORTS {
+            let mut list: LinkedList<Item> = items.iter().copied().collect();
+            sort(&mut list);
+            assert_eq!(
+                list.iter().copied().collect::<Vec<_>>(),
+                expected,
+                "{} sort",
+                name
+            );
+            assert_eq!(list.len(), keys.len());
+        }
+    }
+
+    #[test]
+    fn test_sorts() {
+        check(&[]);
+        check(&[1]);
+        check(&[2, 1]);
+        check(&[5, 1, 4, 2, 3]);
+        check(&[3, 1, 3, 2, 1, 3, 2, 2]);
+        check(&[1, 2, 3, 4, 5, 6, 7]);
+        check(&[9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+        let pseudo_random: Vec<i32> = (0..1000).map(|i| (i * 7919 % 101) - 50).collect();
+        check(&pseudo_random);
+    }
+
+    #[test]
+    fn test_sort_by_key() {
+        let mut list: LinkedList<i32> = [3, -1, -4, 2].into_iter().collect();
+        list.sort_by_key(|v| v.abs());
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![-1, 2, 3, -4]);
+        list.sort();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![-4, -1, 2, 3]);
+    }
+}
@@ -0,0 +1,498 @@
+//! A circular singly linked list: the tail links back to the head.
+//!
+//! Only the tail is stored, the head is always `tail.next`. That is enough
+//! to push at both ends and pop at the front in O(1), and turning the
+//! circle by one step is just moving the tail along.
+//!
+//! Invariants kept by every method:
+//!
+//! - every node is allocated with `Box` and owned by exactly one list
+//! - following `next` from the tail visits all `len` nodes and comes back
+//!   to the tail
+
+mod josephus;
+mod scheduler;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+use crate::{DropObserver, Layout, LinkedListT, Location, NodeLayout, Pointer, PointerKind, Root};
+
+pub use josephus::{josephus, josephus_survivor};
+pub use scheduler::{schedule_round_robin, Task, TimeSlice};
+
+struct Node<T> {
+    value: T,
+    next: NonNull<Node<T>>,
+}
+
+pub struct LinkedList<T> {
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    drop_observer: Option<DropObserver<T>>,
+    // tells the drop checker that we own `Box<Node<T>>`s
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+impl<T> LinkedList<T> {
+    pub const fn new() -> Self {
+        Self {
+            tail: None,
+            len: 0,
+            drop_observer: None,
+            _marker: PhantomData,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn head(&self) -> Option<NonNull<Node<T>>> {
+        // SAFETY: the tail belongs to this list.
+        self.tail.map(|tail| unsafe { (*tail.as_ptr()).next })
+    }
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: nodes stay alive as long as the list is borrowed.
+        self.head().map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: see `front`.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: `&mut self` makes this the only reference into the list.
+        self.head()
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+    /// The node `steps` links after the tail, so `index` steps give the
+    /// node in front of `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list is empty.
+    fn walk(&self, steps: usize) -> NonNull<Node<T>> {
+        let mut node = self.tail.expect("walking an empty list");
+        for _ in 0..steps {
+            // SAFETY: every `next` points at a node of this list.
+            node = unsafe { (*node.as_ptr()).next };
+        }
+        node
+    }
+    /// Links a new node in after `prev`, or makes it the only node if the
+    /// list is empty, and returns it.
+    fn insert_after(&mut self, prev: Option<NonNull<Node<T>>>, value: T) -> NonNull<Node<T>> {
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            value,
+            next: NonNull::dangling(),
+        })));
+        // SAFETY: `prev` belongs to this list and `node` is fresh.
+        unsafe {
+            match prev {
+                Some(prev) => {
+                    (*node.as_ptr()).next = (*prev.as_ptr()).next;
+                    (*prev.as_ptr()).next = node;
+                }
+                None => {
+                    (*node.as_ptr()).next = node;
+                    self.tail = Some(node);
+                }
+            }
+        }
+        self.len += 1;
+        node
+    }
+    /// Unlinks the node after `prev` and gives back ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `prev` must belong to this list.
+    unsafe fn remove_after(&mut self, prev: NonNull<Node<T>>) -> Box<Node<T>> {
+        unsafe {
+            let node = (*prev.as_ptr()).next;
+            if node == prev {
+                self.tail = None;
+            } else {
+                (*prev.as_ptr()).next = (*node.as_ptr()).next;
+                if Some(node) == self.tail {
+                    self.tail = Some(prev);
+                }
+            }
+            self.len -= 1;
+            Box::from_raw(node.as_ptr())
+        }
+    }
+    pub fn push_front(&mut self, value: T) {
+        self.insert_after(self.tail, value);
+    }
+    /// Pushes to the front and moves the tail onto the new node, which
+    /// puts it behind the old tail.
+    pub fn push_back(&mut self, value: T) {
+        let node = self.insert_after(self.tail, value);
+        self.tail = Some(node);
+    }
+    pub fn pop_front(&mut self) -> Option<T> {
+        let tail = self.tail?;
+        // SAFETY: the tail belongs to this list.
+        Some(unsafe { self.remove_after(tail).value })
+    }
+    /// O(n), the node in front of the tail is only found by going around.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let prev = self.walk(self.len.checked_sub(1)?);
+        // SAFETY: `walk` only visits nodes of this list.
+        Some(unsafe { self.remove_after(prev).value })
+    }
+    /// Turns the circle `k` steps forward: the front moves to the back `k`
+    /// times, `1 2 3 4` becomes `3 4 1 2` for `k = 2`. O(k mod n).
+    pub fn rotate_left(&mut self, k: usize) {
+        if self.len > 0 {
+            self.tail = Some(self.walk(k % self.len));
+        }
+    }
+    /// Turns the circle `k` steps back, `1 2 3 4` becomes `4 1 2 3` for
+    /// `k = 1`. O(n - k mod n).
+    pub fn rotate_right(&mut self, k: usize) {
+        if self.len > 0 {
+            self.rotate_left(self.len - k % self.len);
+        }
+    }
+    pub fn clear(&mut self) {
+        while let Some(value) = self.pop_front() {
+            if let Some(observer) = &mut self.drop_observer {
+                observer(&value);
+            }
+        }
+    }
+    /// Iterates once around the circle, from the front to the back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+    /// Goes around the circle forever, starting at the front. Ends right
+    /// away on an empty list.
+    pub fn round_robin(&self) -> RoundRobin<'_, T> {
+        RoundRobin {
+            next: self.head(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+/// Prints the link from the back to the front as well, `1 -> 2 -> back to 1`.
+impl<T: fmt::Display> fmt::Display for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(front) = self.front() else {
+            return write!(f, "None");
+        };
+        for value in self {
+            write!(f, "{} -> ", value)?;
+        }
+        write!(f, "back to {}", front)
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: the list is borrowed for `'a`, so its nodes stay alive.
+        self.next.map(|node| unsafe {
+            self.len -= 1;
+            self.next = Some((*node.as_ptr()).next);
+            &(*node.as_ptr()).value
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    len: usize,
+    _marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `len` stops the walk before it comes around again, so
+        // every node is handed out at most once.
+        self.next.map(|node| unsafe {
+            self.len -= 1;
+            self.next = Some((*node.as_ptr()).next);
+            &mut (*node.as_ptr()).value
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+pub struct RoundRobin<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for RoundRobin<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY: see `Iter::next`.
+        self.next.map(|node| unsafe {
+            self.next = Some((*node.as_ptr()).next);
+            &(*node.as_ptr()).value
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Display + PartialEq> LinkedListT for LinkedList<T> {
+    type Item = T;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn contains(&self, value: &Self::Item) -> bool {
+        self.iter().any(|v| v == value)
+    }
+    fn get(&self, index: usize) -> Option<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.iter().nth(index).cloned()
+    }
+    fn to_vec(&self) -> Vec<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.iter().cloned().collect()
+    }
+    fn push_front(&mut self, value: Self::Item) {
+        self.push_front(value);
+    }
+    fn push_back(&mut self, value: Self::Item) {
+        self.push_back(value);
+    }
+    fn pop_front(&mut self) -> Option<Self::Item> {
+        self.pop_front()
+    }
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        self.pop_back()
+    }
+    fn insert_at(&mut self, index: usize, value: Self::Item) {
+        assert!(index <= self.len, "index {} out of bounds", index);
+        if index == self.len {
+            return self.push_back(value);
+        }
+        let prev = self.walk(index);
+        self.insert_after(Some(prev), value);
+    }
+    fn remove_at(&mut self, index: usize) -> Option<Self::Item> {
+        if index >= self.len {
+            return None;
+        }
+        let prev = self.walk(index);
+        // SAFETY: `walk` only visits nodes of this list.
+        Some(unsafe { self.remove_after(prev).value })
+    }
+    fn remove(&mut self, value: &Self::Item) -> bool {
+        let Some(mut prev) = self.tail else {
+            return false;
+        };
+        for _ in 0..self.len {
+            // SAFETY: `prev` and the node after it belong to this list.
+            unsafe {
+                let node = (*prev.as_ptr()).next;
+                if (*node.as_ptr()).value == *value {
+                    let node = self.remove_after(prev);
+                    if let Some(observer) = &mut self.drop_observer {
+                        observer(&node.value);
+                    }
+                    return true;
+                }
+                prev = node;
+            }
+        }
+        false
+    }
+    fn update(&mut self, old_val: &Self::Item, new_val: Self::Item) -> Option<Self::Item> {
+        let value = self.iter_mut().find(|v| **v == *old_val)?;
+        Some(mem::replace(value, new_val))
+    }
+    fn clear(&mut self) {
+        self.clear();
+    }
+    /// Starts at the node after `tail`, the last node points back to it.
+    fn layout(&self) -> Layout {
+        let pointer = |node: NonNull<Node<T>>| {
+            Pointer::new(
+                PointerKind::NonNull,
+                Location::Address(node.as_ptr() as usize),
+            )
+        };
+        let mut nodes = Vec::with_capacity(self.len);
+        if let Some(tail) = self.tail {
+            // SAFETY: every node in the ring is alive while the list is borrowed
+            let mut current = unsafe { tail.as_ref() }.next;
+            for _ in 0..self.len {
+                let node = unsafe { current.as_ref() };
+                let mut layout = NodeLayout::new(pointer(current).target, &node.value);
+                layout.next = Some(pointer(node.next));
+                nodes.push(layout);
+                current = node.next;
+            }
+        }
+        Layout {
+            roots: vec![Root {
+                name: "tail",
+                link: self.tail.map(pointer),
+            }],
+            nodes,
+        }
+    }
+    fn header(&self) -> &str {
+        "--- Circular Linked List CLI ---"
+    }
+    fn set_drop_observer(&mut self, observer: DropObserver<Self::Item>) {
+        self.drop_observer = Some(observer);
+    }
+}
+
+#[cfg(test)]
+impl<T: fmt::Display + PartialEq + Clone> crate::tests::Snapshot for LinkedList<T> {
+    fn snapshot(&self) -> Vec<T> {
+        let values: Vec<T> = self.iter().cloned().collect();
+        // one more step around has to come back to the front
+        let again: Vec<T> = self
+            .round_robin()
+            .skip(self.len)
+            .take(self.len)
+            .cloned()
+            .collect();
+        assert!(values == again, "the tail doesn't link back to the head");
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+
+    fn values(list: &LinkedList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut list: LinkedList<i32> = (1..=4).collect();
+        list.rotate_left(2);
+        assert_eq!(values(&list), vec![3, 4, 1, 2]);
+        list.rotate_right(1);
+        assert_eq!(values(&list), vec![2, 3, 4, 1]);
+        list.rotate_left(9);
+        assert_eq!(values(&list), vec![3, 4, 1, 2]);
+        list.rotate_right(4);
+        assert_eq!(values(&list), vec![3, 4, 1, 2]);
+        assert_eq!((list.front(), list.back()), (Some(&3), Some(&2)));
+        let mut empty = LinkedList::<i32>::new();
+        empty.rotate_left(3);
+        empty.rotate_right(3);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let list: LinkedList<i32> = (1..=3).collect();
+        let turns: Vec<i32> = list.round_robin().take(7).copied().collect();
+        assert_eq!(turns, vec![1, 2, 3, 1, 2, 3, 1]);
+        assert_eq!(LinkedList::<i32>::new().round_robin().next(), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut list: LinkedList<i32> = (1..=3).collect();
+        assert_eq!(list.to_string(), "1 -> 2 -> 3 -> back to 1");
+        *list.front_mut().unwrap() = 0;
+        list.rotate_left(1);
+        assert_eq!(format!("{:?}", list), "[2, 3, 0]");
+    }
+}
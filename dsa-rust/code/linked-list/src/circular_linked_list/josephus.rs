@@ -0,0 +1,59 @@
+//! The Josephus problem: `n` people stand in a circle and every `k`th one
+//! is counted out until only one is left.
+
+use super::LinkedList;
+
+/// The order in which people `1..=n` leave the circle, the survivor last.
+///
+/// Counting `k` people is turning the circle `k - 1` steps and taking the
+/// one at the front. O(n·k) time, O(n) space.
+///
+/// # Panics
+///
+/// Panics if `k == 0`.
+pub fn josephus(n: usize, k: usize) -> Vec<usize> {
+    assert!(k > 0, "k has to be at least 1");
+    let mut circle: LinkedList<usize> = (1..=n).collect();
+    let mut order = Vec::with_capacity(n);
+    while !circle.is_empty() {
+        circle.rotate_left(k - 1);
+        order.extend(circle.pop_front());
+    }
+    order
+}
+
+/// Only the survivor, from the recurrence `J(1) = 0`,
+/// `J(i) = (J(i - 1) + k) mod i` over 0-based positions. After the first
+/// person leaves, the circle of `i - 1` starts `k` places further on.
+/// O(n) time, O(1) space, `None` for an empty circle.
+///
+/// # Panics
+///
+/// Panics if `k == 0`.
+pub fn josephus_survivor(n: usize, k: usize) -> Option<usize> {
+    assert!(k > 0, "k has to be at least 1");
+    if n == 0 {
+        return None;
+    }
+    let position = (2..=n).fold(0, |survivor, i| (survivor + k) % i);
+    Some(position + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{josephus, josephus_survivor};
+
+    #[test]
+    fn test_josephus() {
+        assert_eq!(josephus(7, 3), vec![3, 6, 2, 7, 5, 1, 4]);
+        assert_eq!(josephus(5, 1), vec![1, 2, 3, 4, 5]);
+        assert_eq!(josephus(1, 4), vec![1]);
+        assert!(josephus(0, 2).is_empty());
+        assert_eq!(josephus_survivor(0, 2), None);
+        for n in 1..20 {
+            for k in 1..8 {
+                assert_eq!(josephus(n, k).last().copied(), josephus_survivor(n, k));
+            }
+        }
+    }
+}
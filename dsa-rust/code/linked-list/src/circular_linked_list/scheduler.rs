@@ -0,0 +1,105 @@
+//! Round-robin CPU scheduling: the ready tasks sit in a circle and each one
+//! runs for at most one time quantum before the next gets its turn.
+
+use std::fmt;
+
+use super::LinkedList;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    pub name: String,
+    /// Time the task still needs to run.
+    pub burst: u32,
+}
+
+impl Task {
+    pub fn new(name: &str, burst: u32) -> Self {
+        Self {
+            name: name.to_owned(),
+            burst,
+        }
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.name, self.burst)
+    }
+}
+
+/// One turn of a task on the CPU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSlice {
+    pub task: String,
+    pub start: u32,
+    pub end: u32,
+    /// Whether the task was done at the end of this slice.
+    pub finished: bool,
+}
+
+/// Runs the tasks, all ready at time 0, in turns of `quantum` and returns
+/// the timeline. A finished task leaves the circle, an unfinished one
+/// stays and the circle turns on to the next.
+///
+/// # Panics
+///
+/// Panics if `quantum == 0`.
+pub fn schedule_round_robin<I>(tasks: I, quantum: u32) -> Vec<TimeSlice>
+where
+    I: IntoIterator<Item = Task>,
+{
+    assert!(quantum > 0, "the quantum has to be at least 1");
+    let mut ready: LinkedList<Task> = tasks.into_iter().filter(|t| t.burst > 0).collect();
+    let mut timeline = Vec::new();
+    let mut time = 0;
+    while let Some(task) = ready.front_mut() {
+        let run = task.burst.min(quantum);
+        task.burst -= run;
+        let finished = task.burst == 0;
+        timeline.push(TimeSlice {
+            task: task.name.clone(),
+            start: time,
+            end: time + run,
+            finished,
+        });
+        time += run;
+        if finished {
+            ready.pop_front();
+        } else {
+            ready.rotate_left(1);
+        }
+    }
+    timeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{schedule_round_robin, Task};
+
+    #[test]
+    fn test_round_robin() {
+        let tasks = [
+            Task::new("A", 5),
+            Task::new("B", 2),
+            Task::new("C", 0),
+            Task::new("D", 3),
+        ];
+        let timeline = schedule_round_robin(tasks, 2);
+        let turns: Vec<(&str, u32, u32, bool)> = timeline
+            .iter()
+            .map(|s| (s.task.as_str(), s.start, s.end, s.finished))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                ("A", 0, 2, false),
+                ("B", 2, 4, true),
+                ("D", 4, 6, false),
+                ("A", 6, 8, false),
+                ("D", 8, 9, true),
+                ("A", 9, 10, true),
+            ]
+        );
+        assert!(schedule_round_robin([], 3).is_empty());
+    }
+}
@@ -0,0 +1,485 @@
+//! Singly linked lists that several threads can change at once.
+//!
+//! [`LinkedList`] locks every node on its own. A thread walking the list
+//! holds the lock of the node it is on and takes the next one before
+//! letting go, hand over hand, so no other thread can unlink a node out
+//! from under it. Everyone takes the locks in list order, which rules out
+//! deadlocks, and threads working at different places in the list don't
+//! wait for each other.
+//!
+//! [`CoarseLinkedList`] is the baseline: one `Mutex` around a whole
+//! [`BoxSingleLinkedList`]. Every operation waits for every other one, but
+//! each takes a single lock instead of one per node it passes.
+
+use std::fmt;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{BoxSingleLinkedList, LinkedListT};
+
+type Link<T> = Option<Arc<Mutex<Node<T>>>>;
+
+struct Node<T> {
+    // only `None` while the node is being unlinked, so that the value can
+    // be moved out without waiting for other threads to drop their `Arc`
+    value: Option<T>,
+    next: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn value(&self) -> &T {
+        self.value.as_ref().expect("node is being unlinked")
+    }
+}
+
+/// A locked node that holds on to its `Arc`, so the lock can be kept after
+/// the link it was reached through is unlocked.
+struct Locked<'a, T> {
+    // declared first, so the lock is released before the `Arc` is dropped
+    guard: MutexGuard<'a, Node<T>>,
+    _node: Arc<Mutex<Node<T>>>,
+}
+
+impl<'a, T> Locked<'a, T> {
+    fn new(node: Arc<Mutex<Node<T>>>) -> Self {
+        let guard = node.lock().unwrap();
+        // SAFETY: the mutex lives in the allocation of `node`, not in the
+        // local, so moving `node` into `Self` doesn't move it. `Self` keeps
+        // the allocation alive for as long as the guard exists.
+        let guard =
+            unsafe { mem::transmute::<MutexGuard<'_, Node<T>>, MutexGuard<'a, Node<T>>>(guard) };
+        Self { guard, _node: node }
+    }
+}
+
+/// The lock held right before the node a walk looks at next.
+enum Prev<'a, T> {
+    Head(MutexGuard<'a, Link<T>>),
+    Node(Locked<'a, T>),
+}
+
+impl<T> Prev<'_, T> {
+    /// The link to the next node.
+    fn link(&mut self) -> &mut Link<T> {
+        match self {
+            Prev::Head(head) => head,
+            Prev::Node(node) => &mut node.guard.next,
+        }
+    }
+}
+
+/// A list with a lock in every node.
+pub struct LinkedList<T> {
+    head: Mutex<Link<T>>,
+    len: AtomicUsize,
+}
+
+impl<T> LinkedList<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: Mutex::new(None),
+            len: AtomicUsize::new(0),
+        }
+    }
+    /// Only a snapshot, other threads may change it right after.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::SeqCst)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn lock_head(&self) -> Prev<'_, T> {
+        Prev::Head(self.head.lock().unwrap())
+    }
+    /// Links `value` in right after `prev`, which is locked.
+    fn link_after(&self, prev: &mut Prev<'_, T>, value: T) {
+        let link = prev.link();
+        let node = Node {
+            value: Some(value),
+            next: link.take(),
+        };
+        *link = Some(Arc::new(Mutex::new(node)));
+        self.len.fetch_add(1, Ordering::SeqCst);
+    }
+    /// Unlinks `current`, the node right after `prev`. Both are locked.
+    ///
+    /// `current` is borrowed rather than moved in: a guard that is a
+    /// function argument must not outlive the allocation it points into,
+    /// and dropping `current` here could free that.
+    fn unlink(&self, prev: &mut Prev<'_, T>, current: &mut Locked<'_, T>) -> T {
+        *prev.link() = current.guard.next.take();
+        self.len.fetch_sub(1, Ordering::SeqCst);
+        current.guard.value.take().unwrap()
+    }
+    /// Walks hand over hand until `stop` returns `true` for the index and
+    /// value of the next node. Gives back the lock before that node, or
+    /// before the end of the list, and the index of the position after it.
+    fn walk(&self, mut stop: impl FnMut(usize, &T) -> bool) -> (Prev<'_, T>, usize) {
+        let mut prev = self.lock_head();
+        let mut index = 0;
+        while let Some(next) = prev.link().clone() {
+            let current = Locked::new(next);
+            if stop(index, current.guard.value()) {
+                break;
+            }
+            // the old lock is only released once the new one is held
+            prev = Prev::Node(current);
+            index += 1;
+        }
+        (prev, index)
+    }
+    /// O(1), only the head is locked.
+    pub fn push_front(&self, value: T) {
+        let mut head = self.lock_head();
+        self.link_after(&mut head, value);
+    }
+    /// O(1), only the head and the first node are locked.
+    pub fn pop_front(&self) -> Option<T> {
+        let mut head = self.lock_head();
+        let first = head.link().clone()?;
+        let mut first = Locked::new(first);
+        Some(self.unlink(&mut head, &mut first))
+    }
+    pub fn push_back(&self, value: T) {
+        let (mut last, _) = self.walk(|_, _| false);
+        self.link_after(&mut last, value);
+    }
+    /// Inserts `value` at `index`, or hands it back if the list is shorter
+    /// than `index` by the time the walk gets there.
+    pub fn insert_at(&self, index: usize, value: T) -> Result<(), T> {
+        let (mut prev, at) = self.walk(|i, _| i == index);
+        if at != index {
+            return Err(value);
+        }
+        self.link_after(&mut prev, value);
+        Ok(())
+    }
+    pub fn remove_at(&self, index: usize) -> Option<T> {
+        self.remove_where(|i, _| i == index)
+    }
+    /// Removes the first element for which `matches` returns `true`.
+    fn remove_where(&self, mut matches: impl FnMut(usize, &T) -> bool) -> Option<T> {
+        let mut prev = self.lock_head();
+        let mut index = 0;
+        while let Some(next) = prev.link().clone() {
+            let mut current = Locked::new(next);
+            if matches(index, current.guard.value()) {
+                return Some(self.unlink(&mut prev, &mut current));
+            }
+            prev = Prev::Node(current);
+            index += 1;
+        }
+        None
+    }
+    /// Removes the first element equal to `value`.
+    pub fn remove(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.remove_where(|_, v| v == value).is_some()
+    }
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        let mut found = false;
+        self.walk(|_, v| {
+            found = v == value;
+            found
+        });
+        found
+    }
+    /// Copies the values, one node after the other.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut values = Vec::with_capacity(self.len());
+        self.walk(|_, v| {
+            values.push(v.clone());
+            false
+        });
+        values
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// `&mut self` means no other thread holds a node anymore, so every node
+// can be unwrapped. One at a time, the default drop would recurse.
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        let mut current = self
+            .head
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        while let Some(node) = current.and_then(Arc::into_inner) {
+            current = node
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .next;
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut result = Ok(());
+        self.walk(|_, v| {
+            result = write!(f, "{} -> ", v);
+            result.is_err()
+        });
+        result?;
+        write!(f, "None")
+    }
+}
+
+/// A [`BoxSingleLinkedList`] behind a single lock.
+pub struct CoarseLinkedList<T: fmt::Display + PartialEq> {
+    list: Mutex<BoxSingleLinkedList<T>>,
+}
+
+impl<T: fmt::Display + PartialEq> CoarseLinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            list: Mutex::new(BoxSingleLinkedList::new()),
+        }
+    }
+    fn lock(&self) -> MutexGuard<'_, BoxSingleLinkedList<T>> {
+        self.list.lock().unwrap()
+    }
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+    pub fn push_front(&self, value: T) {
+        self.lock().push_front(value);
+    }
+    pub fn pop_front(&self) -> Option<T> {
+        self.lock().pop_front()
+    }
+    pub fn push_back(&self, value: T) {
+        self.lock().push_back(value);
+    }
+    pub fn insert_at(&self, index: usize, value: T) -> Result<(), T> {
+        let mut list = self.lock();
+        if index > list.len() {
+            return Err(value);
+        }
+        list.insert_at(index, value);
+        Ok(())
+    }
+    pub fn remove_at(&self, index: usize) -> Option<T> {
+        self.lock().remove_at(index)
+    }
+    pub fn remove(&self, value: &T) -> bool {
+        self.lock().remove(value)
+    }
+    pub fn contains(&self, value: &T) -> bool {
+        self.lock().contains(value)
+    }
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.lock().iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Display + PartialEq> Default for CoarseLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Display + PartialEq> fmt::Display for CoarseLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.lock().fmt(f)
+    }
+}
+
+/// What both lists offer, so the same stress tests and benchmarks run on
+/// either.
+pub trait ConcurrentList<T>: Default + Send + Sync {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn push_front(&self, value: T);
+    fn pop_front(&self) -> Option<T>;
+    fn push_back(&self, value: T);
+    fn insert_at(&self, index: usize, value: T) -> Result<(), T>;
+    fn remove_at(&self, index: usize) -> Option<T>;
+    fn remove(&self, value: &T) -> bool;
+    fn contains(&self, value: &T) -> bool;
+    fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone;
+}
+
+macro_rules! impl_concurrent_list {
+    ($list:ident, $($bounds:tt)*) => {
+        impl<T: $($bounds)*> ConcurrentList<T> for $list<T> {
+            fn len(&self) -> usize {
+                self.len()
+            }
+            fn push_front(&self, value: T) {
+                self.push_front(value)
+            }
+            fn pop_front(&self) -> Option<T> {
+                self.pop_front()
+            }
+            fn push_back(&self, value: T) {
+                self.push_back(value)
+            }
+            fn insert_at(&self, index: usize, value: T) -> Result<(), T> {
+                self.insert_at(index, value)
+            }
+            fn remove_at(&self, index: usize) -> Option<T> {
+                self.remove_at(index)
+            }
+            fn remove(&self, value: &T) -> bool {
+                self.remove(value)
+            }
+            fn contains(&self, value: &T) -> bool {
+                self.contains(value)
+            }
+            fn to_vec(&self) -> Vec<T>
+            where
+                T: Clone,
+            {
+                self.to_vec()
+            }
+        }
+    };
+}
+
+impl_concurrent_list!(LinkedList, PartialEq + Send);
+impl_concurrent_list!(CoarseLinkedList, fmt::Display + PartialEq + Send);
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{CoarseLinkedList, ConcurrentList, LinkedList};
+
+    fn single_thread<L: ConcurrentList<i32>>() {
+        let list = L::default();
+        list.push_back(2);
+        list.push_front(0);
+        assert_eq!(list.insert_at(1, 1), Ok(()));
+        assert_eq!(list.insert_at(3, 3), Ok(()));
+        assert_eq!(list.insert_at(9, 9), Err(9));
+        assert_eq!(list.to_vec(), vec![0, 1, 2, 3]);
+        assert!(list.contains(&3));
+        assert!(!list.contains(&9));
+        assert_eq!(list.remove_at(1), Some(1));
+        assert_eq!(list.remove_at(3), None);
+        assert!(list.remove(&3));
+        assert!(!list.remove(&3));
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.to_vec(), vec![2]);
+        assert_eq!(list.len(), 1);
+    }
+
+    /// Every thread adds its own values at the front, the back and in the
+    /// middle, then takes half of them out again while the others are busy.
+    fn stress<L: ConcurrentList<i32>>(threads: i32, per_thread: i32) {
+        let list = L::default();
+        thread::scope(|s| {
+            for t in 0..threads {
+                let list = &list;
+                s.spawn(move || {
+                    let base = t * per_thread;
+                    for i in base..base + per_thread {
+                        match i % 3 {
+                            0 => list.push_front(i),
+                            1 => list.push_back(i),
+                            _ => {
+                                let index = list.len() / 2;
+                                if let Err(i) = list.insert_at(index, i) {
+                                    list.push_back(i);
+                                }
+                            }
+                        }
+                    }
+                    for i in (base..base + per_thread).step_by(2) {
+                        assert!(list.remove(&i), "{} went missing", i);
+                    }
+                });
+            }
+        });
+        let mut values = list.to_vec();
+        values.sort();
+        let expected: Vec<i32> = (0..threads * per_thread).filter(|i| i % 2 == 1).collect();
+        assert_eq!(values, expected);
+        assert_eq!(list.len(), expected.len());
+    }
+
+    #[test]
+    fn test_fine_grained() {
+        single_thread::<LinkedList<i32>>();
+        let list = LinkedList::new();
+        list.push_back(1);
+        list.push_back(2);
+        assert_eq!(list.to_string(), "1 -> 2 -> None");
+    }
+
+    #[test]
+    fn test_coarse_grained() {
+        single_thread::<CoarseLinkedList<i32>>();
+    }
+
+    #[test]
+    fn test_fine_grained_stress() {
+        let per_thread = if cfg!(miri) { 10 } else { 300 };
+        stress::<LinkedList<i32>>(4, per_thread);
+    }
+
+    #[test]
+    fn test_coarse_grained_stress() {
+        let per_thread = if cfg!(miri) { 10 } else { 300 };
+        stress::<CoarseLinkedList<i32>>(4, per_thread);
+    }
+
+    /// Front and back work on different nodes and don't block each other
+    /// once the list is long enough.
+    #[test]
+    fn test_pop_while_appending() {
+        let list = LinkedList::new();
+        for i in 0..100 {
+            list.push_back(i);
+        }
+        let popped = thread::scope(|s| {
+            let popper = s.spawn(|| {
+                (0..100)
+                    .filter_map(|_| list.pop_front())
+                    .collect::<Vec<_>>()
+            });
+            for i in 100..200 {
+                list.push_back(i);
+            }
+            popper.join().unwrap()
+        });
+        assert_eq!(popped, (0..100).collect::<Vec<_>>());
+        assert_eq!(list.to_vec(), (100..200).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_drop_long_list() {
+        let list = LinkedList::new();
+        for i in 0..1_000_000 {
+            list.push_front(i);
+        }
+        drop(list);
+    }
+}
@@ -0,0 +1,131 @@
+//! The element types the binary can put into a list.
+
+use std::{fmt, str::FromStr};
+
+/// With the `serde` feature an element can also be saved as JSON or CSV.
+#[cfg(feature = "serde")]
+pub trait MaybeSerde: serde::Serialize + serde::de::DeserializeOwned {}
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> MaybeSerde for T {}
+#[cfg(not(feature = "serde"))]
+pub trait MaybeSerde {}
+#[cfg(not(feature = "serde"))]
+impl<T> MaybeSerde for T {}
+
+/// A value the menu and scripts can read, print, sort, look for and save.
+pub trait Element: FromStr + fmt::Display + PartialOrd + Clone + MaybeSerde {
+    /// How error messages call the type.
+    const NAME: &'static str;
+
+    /// Whether `self` counts as `other` when looking for a value.
+    ///
+    /// Only floats use `tolerance`, everything else is compared exactly.
+    fn matches(&self, other: &Self, _tolerance: f64) -> bool {
+        self == other
+    }
+}
+
+impl Element for i32 {
+    const NAME: &'static str = "i32";
+}
+
+impl Element for i64 {
+    const NAME: &'static str = "i64";
+}
+
+impl Element for f64 {
+    const NAME: &'static str = "f64";
+
+    /// `0.1 + 0.2` is not `0.3`, with a tolerance of `1e-9` it matches.
+    fn matches(&self, other: &Self, tolerance: f64) -> bool {
+        self == other || (self - other).abs() <= tolerance
+    }
+}
+
+impl Element for String {
+    const NAME: &'static str = "String";
+}
+
+impl Element for char {
+    const NAME: &'static str = "char";
+}
+
+/// An [`Element`] picked at runtime, e.g. from the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ElementType {
+    #[default]
+    I32,
+    I64,
+    F64,
+    String,
+    Char,
+}
+
+impl ElementType {
+    pub const ALL: [Self; 5] = [Self::I32, Self::I64, Self::F64, Self::String, Self::Char];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::I32 => i32::NAME,
+            Self::I64 => i64::NAME,
+            Self::F64 => f64::NAME,
+            Self::String => String::NAME,
+            Self::Char => char::NAME,
+        }
+    }
+}
+
+impl fmt::Display for ElementType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ElementType {
+    type Err = String;
+
+    /// Parses the name of the type, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|ty| ty.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|ty| ty.name()).collect();
+                format!(
+                    "unknown element type '{}', expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Element, ElementType};
+
+    #[test]
+    fn test_matches() {
+        assert!(!(0.1 + 0.2).matches(&0.3, 0.0));
+        assert!((0.1 + 0.2).matches(&0.3, 1e-9));
+        assert!(!1.0.matches(&1.1, 0.05));
+        assert!(f64::INFINITY.matches(&f64::INFINITY, 0.0));
+        assert!(!f64::NAN.matches(&f64::NAN, 1.0));
+        // only floats have a tolerance
+        assert!(!1.matches(&2, 5.0));
+        assert!('a'.matches(&'a', 0.0));
+    }
+
+    #[test]
+    fn test_element_type() {
+        for ty in ElementType::ALL {
+            assert_eq!(ty.to_string().parse::<ElementType>(), Ok(ty));
+        }
+        assert_eq!("string".parse::<ElementType>(), Ok(ElementType::String));
+        assert_eq!("F64".parse::<ElementType>(), Ok(ElementType::F64));
+        assert_eq!(
+            "u8".parse::<ElementType>(),
+            Err("unknown element type 'u8', expected one of i32, i64, f64, String, char".into())
+        );
+    }
+}
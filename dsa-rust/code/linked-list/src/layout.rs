@@ -0,0 +1,343 @@
+//! A snapshot of how a list sits in memory: where every node lives, what
+//! kind of pointer links it to its neighbours and, for the `Rc` lists, how
+//! many references keep it alive.
+//!
+//! `Display` draws it as boxes and arrows, [`Layout::to_dot`] writes it
+//! for Graphviz:
+//!
+//! ```text
+//! head ─Box─▶ 0x5581a2c0 (#0)
+//! ┌──────────────────────────────┐
+//! │ #0 @ 0x5581a2c0              │
+//! │ value: 1                     │
+//! │ next: Box ─▶ 0x5581a2e0 (#1) │
+//! └──────────────────────────────┘
+//!                │
+//!                ▼
+//! ┌──────────────────────────────┐
+//! │ #1 @ 0x5581a2e0              │
+//! │ value: 2                     │
+//! │ next: None                   │
+//! └──────────────────────────────┘
+//! ```
+
+use std::fmt::{self, Write};
+
+use crate::RcCounts;
+
+/// Where a node lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// The address of a node on the heap.
+    Address(usize),
+    /// The index of a node in the `Vec` of an arena.
+    Slot(usize),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "{:#x}", address),
+            Self::Slot(index) => write!(f, "slot {}", index),
+        }
+    }
+}
+
+/// The kind of pointer behind a link, which says who owns the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    /// The only owner.
+    Box,
+    /// One of the owners.
+    Rc,
+    /// Not an owner, the node may already be gone.
+    Weak,
+    /// A raw pointer, the list owns all nodes and frees them itself.
+    NonNull,
+    /// An index into the arena, which owns every node.
+    Index,
+}
+
+impl fmt::Display for PointerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Box => "Box",
+            Self::Rc => "Rc",
+            Self::Weak => "Weak",
+            Self::NonNull => "NonNull",
+            Self::Index => "index",
+        })
+    }
+}
+
+/// A link to a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pointer {
+    pub kind: PointerKind,
+    pub target: Location,
+}
+
+impl Pointer {
+    pub fn new(kind: PointerKind, target: Location) -> Self {
+        Self { kind, target }
+    }
+}
+
+/// A field of the list itself, like `head`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Root {
+    pub name: &'static str,
+    pub link: Option<Pointer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeLayout {
+    pub location: Location,
+    pub value: String,
+    pub next: Option<Pointer>,
+    pub prev: Option<Pointer>,
+    /// Only for nodes behind an `Rc`.
+    pub counts: Option<RcCounts>,
+}
+
+impl NodeLayout {
+    /// A node without links, they are filled in afterwards.
+    pub fn new(location: Location, value: &impl fmt::Display) -> Self {
+        Self {
+            location,
+            value: value.to_string(),
+            next: None,
+            prev: None,
+            counts: None,
+        }
+    }
+}
+
+/// The nodes of a list from the front, see [`LinkedListT::layout`].
+///
+/// [`LinkedListT::layout`]: crate::LinkedListT::layout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub roots: Vec<Root>,
+    pub nodes: Vec<NodeLayout>,
+}
+
+impl Layout {
+    /// Where `location` is in the list.
+    fn index_of(&self, location: Location) -> Option<usize> {
+        self.nodes.iter().position(|node| node.location == location)
+    }
+
+    /// `0x5581a2e0 (#1)`, or just the location of a node that isn't part
+    /// of the list.
+    fn target(&self, link: Pointer) -> String {
+        match self.index_of(link.target) {
+            Some(index) => format!("{} (#{})", link.target, index),
+            None => link.target.to_string(),
+        }
+    }
+
+    fn link_line(&self, name: &str, link: Option<Pointer>) -> String {
+        match link {
+            Some(link) => format!("{}: {} ─▶ {}", name, link.kind, self.target(link)),
+            None => format!("{}: None", name),
+        }
+    }
+
+    /// The layout as a Graphviz graph, `dot -Tsvg` draws it.
+    ///
+    /// Links that don't own their node are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph list {\n    rankdir=LR;\n    node [shape=record];\n");
+        let id = |location: Location| match self.index_of(location) {
+            Some(index) => format!("n{}", index),
+            None => format!("\"{}\"", location),
+        };
+        let edge = |dot: &mut String, from: &str, link: Pointer| {
+            let style = match link.kind {
+                PointerKind::Weak | PointerKind::NonNull | PointerKind::Index => ", style=dashed",
+                PointerKind::Box | PointerKind::Rc => "",
+            };
+            writeln!(
+                dot,
+                "    {} -> {} [label=\"{}\"{}];",
+                from,
+                id(link.target),
+                link.kind,
+                style
+            )
+            .unwrap();
+        };
+        for root in &self.roots {
+            writeln!(dot, "    {} [shape=plaintext];", root.name).unwrap();
+            if let Some(link) = root.link {
+                edge(&mut dot, root.name, link);
+            }
+        }
+        for (index, node) in self.nodes.iter().enumerate() {
+            let mut label = format!(
+                "#{} | {} | {}",
+                index,
+                escape(&node.location.to_string()),
+                escape(&node.value)
+            );
+            if let Some(counts) = node.counts {
+                write!(label, " | strong {}, weak {}", counts.strong, counts.weak).unwrap();
+            }
+            writeln!(dot, "    n{} [label=\"{{{}}}\"];", index, label).unwrap();
+            let from = format!("n{}", index);
+            for link in node.next.iter().chain(&node.prev) {
+                edge(&mut dot, &from, *link);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Escapes what a record label would read as its own syntax.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '"' | '\\' | '{' | '}' | '|' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        for root in &self.roots {
+            lines.push(match root.link {
+                Some(link) => format!("{} ─{}─▶ {}", root.name, link.kind, self.target(link)),
+                None => format!("{}: None", root.name),
+            });
+        }
+        for (index, node) in self.nodes.iter().enumerate() {
+            let mut content = vec![
+                format!("#{} @ {}", index, node.location),
+                format!("value: {}", node.value),
+                self.link_line("next", node.next),
+            ];
+            if node.prev.is_some() {
+                content.push(self.link_line("prev", node.prev));
+            }
+            if let Some(counts) = node.counts {
+                content.push(format!("strong {}, weak {}", counts.strong, counts.weak));
+            }
+            let width = content
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap();
+            lines.push(format!("┌{}┐", "─".repeat(width + 2)));
+            for line in content {
+                let padding = " ".repeat(width - line.chars().count());
+                lines.push(format!("│ {}{} │", line, padding));
+            }
+            lines.push(format!("└{}┘", "─".repeat(width + 2)));
+            // an arrow down to the next box if that is where `next` goes
+            let below = self.nodes.get(index + 1).map(|below| below.location);
+            if below.is_some() && node.next.map(|link| link.target) == below {
+                let middle = " ".repeat(width / 2 + 1);
+                lines.push(format!("{}│", middle));
+                lines.push(format!("{}▼", middle));
+            }
+        }
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layout, Location, NodeLayout, Pointer, PointerKind, Root};
+    use crate::RcCounts;
+
+    /// Two Rc nodes linked both ways, the second one closing a cycle.
+    fn layout() -> Layout {
+        let (a, b) = (Location::Address(0x10), Location::Address(0x20));
+        let mut first = NodeLayout::new(a, &1);
+        first.next = Some(Pointer::new(PointerKind::Rc, b));
+        first.counts = Some(RcCounts { strong: 2, weak: 0 });
+        let mut second = NodeLayout::new(b, &"a|b");
+        second.next = Some(Pointer::new(PointerKind::Rc, a));
+        second.prev = Some(Pointer::new(PointerKind::Weak, a));
+        second.counts = Some(RcCounts { strong: 1, weak: 1 });
+        Layout {
+            roots: vec![
+                Root {
+                    name: "head",
+                    link: Some(Pointer::new(PointerKind::Rc, a)),
+                },
+                Root {
+                    name: "tail",
+                    link: None,
+                },
+            ],
+            nodes: vec![first, second],
+        }
+    }
+
+    #[test]
+    fn test_diagram() {
+        let expected = "\
+head ─Rc─▶ 0x10 (#0)
+tail: None
+┌───────────────────────┐
+│ #0 @ 0x10             │
+│ value: 1              │
+│ next: Rc ─▶ 0x20 (#1) │
+│ strong 2, weak 0      │
+└───────────────────────┘
+           │
+           ▼
+┌─────────────────────────┐
+│ #1 @ 0x20               │
+│ value: a|b              │
+│ next: Rc ─▶ 0x10 (#0)   │
+│ prev: Weak ─▶ 0x10 (#0) │
+│ strong 1, weak 1        │
+└─────────────────────────┘";
+        assert_eq!(layout().to_string(), expected);
+    }
+
+    #[test]
+    fn test_dot() {
+        let expected = "\
+digraph list {
+    rankdir=LR;
+    node [shape=record];
+    head [shape=plaintext];
+    head -> n0 [label=\"Rc\"];
+    tail [shape=plaintext];
+    n0 [label=\"{#0 | 0x10 | 1 | strong 2, weak 0}\"];
+    n0 -> n1 [label=\"Rc\"];
+    n1 [label=\"{#1 | 0x20 | a\\|b | strong 1, weak 1}\"];
+    n1 -> n0 [label=\"Rc\"];
+    n1 -> n0 [label=\"Weak\", style=dashed];
+}
+";
+        assert_eq!(layout().to_dot(), expected);
+    }
+
+    #[test]
+    fn test_empty_and_slots() {
+        let empty = Layout {
+            roots: vec![Root {
+                name: "head",
+                link: None,
+            }],
+            nodes: Vec::new(),
+        };
+        assert_eq!(empty.to_string(), "head: None");
+        let node = NodeLayout::new(Location::Slot(3), &'x');
+        let arena = Layout {
+            roots: Vec::new(),
+            nodes: vec![node],
+        };
+        assert!(arena.to_string().contains("#0 @ slot 3"));
+    }
+}
@@ -1,32 +1,224 @@
+mod arena_linked_list;
 mod box_single_linked_list;
+mod circular_linked_list;
+mod concurrent_linked_list;
+mod element;
+mod layout;
+mod persistent_list;
+mod raw_double_linked_list;
+mod rc_double_linked_list;
 mod rc_single_linked_list;
+mod script;
+mod skip_list;
+mod storage;
+#[cfg(test)]
+mod tests;
+mod unrolled_linked_list;
 
 use std::{
+    cmp::Ordering,
     fmt,
     io::{self, Write},
-    str::FromStr,
 };
 
-pub use box_single_linked_list::LinkedList as BoxSingleLinkedList;
-pub use rc_single_linked_list::LinkedList as RcSingleLinkedList;
+pub use arena_linked_list::{
+    CacheStats, Clock, Handle, LfuCache, LinkedList as ArenaLinkedList, LruCache, ManualClock,
+    SystemClock,
+};
+pub use box_single_linked_list::{LinkedList as BoxSingleLinkedList, Polynomial};
+pub use circular_linked_list::{
+    josephus, josephus_survivor, schedule_round_robin, LinkedList as CircularLinkedList, Task,
+    TimeSlice,
+};
+pub use concurrent_linked_list::{
+    CoarseLinkedList, ConcurrentList, LinkedList as ConcurrentLinkedList,
+};
+pub use element::{Element, ElementType};
+pub use layout::{Layout, Location, NodeLayout, Pointer, PointerKind, Root};
+pub use persistent_list::List as PersistentList;
+pub use raw_double_linked_list::{BigUint, Cursor, CursorMut, LinkedList as RawDoubleLinkedList};
+pub use rc_double_linked_list::LinkedList as RcDoubleLinkedList;
+pub use rc_single_linked_list::{Cycle, LinkedList as RcSingleLinkedList, RcCounts};
+pub use script::{run_script, ScriptStatus};
+pub use skip_list::{LevelHistogram, SkipList};
+pub use storage::{load, read_values, save, save_file, Format, StorageError};
+pub use unrolled_linked_list::UnrolledLinkedList;
+
+use script::Command;
 
-pub trait LinkedListT {
+/// Called with every value a list drops, see [`LinkedListT::set_drop_observer`].
+///
+/// It has to be `Send`, so that a list holding one can still be moved to
+/// another thread, like [`CoarseLinkedList`] needs.
+pub type DropObserver<T> = Box<dyn FnMut(&T) + Send>;
+
+/// The operations every list in this crate supports.
+///
+/// Lists are printed through `Display`, e.g. `1 -> 2 -> None`.
+pub trait LinkedListT: fmt::Display {
     type Item: fmt::Display + PartialEq;
     fn new() -> Self;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn contains(&self, value: &Self::Item) -> bool;
+    /// A copy of the element at `index`. The `Rc` lists can't hand out a
+    /// plain reference into a `RefCell`, so every list returns a clone.
+    fn get(&self, index: usize) -> Option<Self::Item>
+    where
+        Self::Item: Clone;
+    /// Copies of the elements from front to back. The default calls `get`
+    /// for every index, which walks the list again each time, so every list
+    /// overrides it with a single pass of its own iterator.
+    fn to_vec(&self) -> Vec<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        (0..self.len())
+            .filter_map(|index| self.get(index))
+            .collect()
+    }
     fn push_front(&mut self, value: Self::Item);
     fn push_back(&mut self, value: Self::Item);
-    fn remove(&mut self, value: Self::Item);
-    fn update(&mut self, old_val: Self::Item, new_val: Self::Item);
-    fn print(&self);
+    fn pop_front(&mut self) -> Option<Self::Item>;
+    fn pop_back(&mut self) -> Option<Self::Item>;
+    /// Inserts `value` so that it ends up at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    fn insert_at(&mut self, index: usize, value: Self::Item);
+    /// Removes the element at `index`, `None` if `index` is out of bounds.
+    fn remove_at(&mut self, index: usize) -> Option<Self::Item>;
+    /// Removes the first element equal to `value`, returns whether one was found.
+    fn remove(&mut self, value: &Self::Item) -> bool;
+    /// Replaces the first element equal to `old_val`, returning the value
+    /// it replaced or `None` if there was no such element.
+    fn update(&mut self, old_val: &Self::Item, new_val: Self::Item) -> Option<Self::Item>;
+    fn clear(&mut self);
+    /// Sorts the list in ascending order, keeping equal elements in their
+    /// original order. Elements that can't be compared, like a NaN, count
+    /// as equal to everything.
+    ///
+    /// The default moves the values out into a `Vec`, sorts that and puts
+    /// them back.
+    fn sort(&mut self)
+    where
+        Self::Item: PartialOrd,
+    {
+        let mut values = Vec::with_capacity(self.len());
+        while let Some(value) = self.pop_front() {
+            values.push(value);
+        }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        for value in values {
+            self.push_back(value);
+        }
+    }
+    /// Where the nodes are in memory and how they are linked, for a
+    /// closer look than `Display` gives.
+    fn layout(&self) -> Layout;
     fn header(&self) -> &str;
+    /// Opts in to being told about every node the list drops, either when
+    /// it is removed or when the whole list goes away.
+    ///
+    /// Lists that don't track their drops ignore the observer.
+    fn set_drop_observer(&mut self, _observer: DropObserver<Self::Item>) {}
 }
 
-pub fn show_list<U, T>()
+/// Settings shared by the menu and scripts.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// How far apart two floats may be and still count as the same value
+    /// when removing or updating by value. 0 compares exactly.
+    pub tolerance: f64,
+    /// Whether the menu waits for Enter after every step and clears the
+    /// screen. On by default.
+    pub clear_screen: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.0,
+            clear_screen: true,
+        }
+    }
+}
+
+/// Reads one trimmed line, `None` once stdin is closed or can't be read.
+pub fn prompt(message: &str) -> Option<String> {
+    print!("{}", message);
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_owned()),
+    }
+}
+
+/// Asks for the arguments of the menu entry `choice`. `Err(None)` once
+/// stdin is closed, `Err(Some(_))` for an invalid choice or argument.
+fn read_command<U: Element>(choice: &str) -> Result<Command<U>, Option<String>> {
+    fn value<U: Element>(message: &str) -> Result<U, Option<String>> {
+        prompt(message).ok_or(None)?.parse().map_err(|_| {
+            Some(format!(
+                "Invalid input. Please enter a value of type {}.",
+                U::NAME
+            ))
+        })
+    }
+    fn index(message: &str) -> Result<usize, Option<String>> {
+        prompt(message)
+            .ok_or(None)?
+            .parse()
+            .map_err(|_| Some("Invalid index.".to_owned()))
+    }
+    Ok(match choice {
+        "1" => Command::PushFront(value("Enter value to add: ")?),
+        "2" => Command::PushBack(value("Enter value to add: ")?),
+        "3" => Command::Remove(value("Enter value to remove: ")?),
+        "4" => {
+            let old_value = value("Enter value to update: ")?;
+            Command::Update(old_value, value("Enter new value: ")?)
+        }
+        "5" => Command::Print,
+        "6" => {
+            let index = index("Enter index: ")?;
+            Command::InsertAt(index, value("Enter value to insert: ")?)
+        }
+        "7" => Command::RemoveAt(index("Enter index: ")?),
+        "8" => Command::Get(index("Enter index: ")?),
+        "9" => Command::PopFront,
+        "10" => Command::PopBack,
+        "11" => Command::Len,
+        "12" => Command::Clear,
+        "13" => Command::Sort,
+        "14" => Command::Layout,
+        "15" => Command::Dot,
+        "16" => Command::Save(prompt("Enter file name: ").ok_or(None)?, None),
+        "17" => Command::Load(prompt("Enter file name: ").ok_or(None)?, None),
+        "18" => Command::Exit,
+        _ => {
+            return Err(Some(
+                "Invalid choice. Please enter a valid option.".to_owned(),
+            ))
+        }
+    })
+}
+
+/// The interactive menu for a list of `T`. Stops at "Exit" or once stdin
+/// is closed; [`run_script`] runs the same operations without the menu.
+pub fn show_list<U, T>(options: &Options)
 where
     T: LinkedListT<Item = U>,
-    U: FromStr + fmt::Display + PartialEq,
+    U: Element,
 {
     let mut list = T::new();
+    list.set_drop_observer(Box::new(|value| {
+        println!("Node with value {} has been dropped.", value)
+    }));
     loop {
         println!("{}", list.header());
         println!("1. Add node");
@@ -34,72 +226,137 @@ where
         println!("3. Remove node");
         println!("4. Update node");
         println!("5. Print list");
-        println!("6. Exit");
-        print!("Enter your choice: ");
-        io::stdout().flush().unwrap();
-
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        let choice = input.trim().parse::<u8>().unwrap_or(0);
+        println!("6. Insert at index");
+        println!("7. Remove at index");
+        println!("8. Get at index");
+        println!("9. Pop front");
+        println!("10. Pop back");
+        println!("11. Length");
+        println!("12. Clear");
+        println!("13. Sort list");
+        println!("14. Memory layout");
+        println!("15. Graphviz DOT");
+        println!("16. Save to file");
+        println!("17. Load from file");
+        println!("18. Exit");
+        let Some(choice) = prompt("Enter your choice: ") else {
+            break;
+        };
 
-        match choice {
-            1 => {
-                print!("Enter value to add: ");
-                io::stdout().flush().unwrap();
-                input.clear();
-                io::stdin().read_line(&mut input).unwrap();
-                if let Ok(value) = input.trim().parse::<U>() {
-                    list.push_front(value);
-                } else {
-                    println!("Invalid input. Please enter an integer.");
-                }
+        match read_command::<U>(&choice) {
+            Ok(Command::Exit) => {
+                println!("Exiting...");
+                break;
             }
-            2 => {
-                print!("Enter value to add: ");
-                io::stdout().flush().unwrap();
-                input.clear();
-                io::stdin().read_line(&mut input).unwrap();
-                if let Ok(value) = input.trim().parse::<U>() {
-                    list.push_back(value);
-                } else {
-                    println!("Invalid input. Please enter an integer.");
+            Ok(command) => {
+                if let Command::Print = command {
+                    println!("\nLinked List:");
                 }
-            }
-            3 => {
-                print!("Enter value to remove: ");
-                io::stdout().flush().unwrap();
-                input.clear();
-                io::stdin().read_line(&mut input).unwrap();
-                if let Ok(value) = input.trim().parse::<U>() {
-                    list.remove(value);
-                } else {
-                    println!("Invalid input. Please enter an integer.");
+                match command.execute(&mut list, options) {
+                    Ok(Some(output)) => println!("{}", output),
+                    Ok(None) => {}
+                    Err(message) => eprintln!("{}", message),
                 }
             }
-            4 => {
-                print!("Enter value to update: ");
-                io::stdout().flush().unwrap();
-                input.clear();
-                io::stdin().read_line(&mut input).unwrap();
-                if let Ok(old_value) = input.trim().parse::<U>() {
-                    print!("Enter new value: ");
-                    io::stdout().flush().unwrap();
-                    input.clear();
-                    io::stdin().read_line(&mut input).unwrap();
-                    if let Ok(new_value) = input.trim().parse::<U>() {
-                        list.update(old_value, new_value);
-                    } else {
-                        println!("Invalid input. Please enter an integer.");
-                    }
+            Err(Some(message)) => eprintln!("{}", message),
+            Err(None) => break,
+        }
+        if wait_and_clear(options).is_none() {
+            break;
+        }
+    }
+}
+
+/// Waits for Enter and clears the screen with ANSI escapes, `None` once
+/// stdin is closed. Without clearing there is nothing to wait for.
+fn wait_and_clear(options: &Options) -> Option<()> {
+    if options.clear_screen {
+        prompt("Press Enter to Continue...")?;
+        // erase the screen and move the cursor to the top left corner
+        print!("\x1b[2J\x1b[H");
+        let _ = io::stdout().flush();
+    }
+    Some(())
+}
+
+/// Reads a whitespace separated list of integers.
+fn prompt_values(message: &str) -> Option<BoxSingleLinkedList<i32>> {
+    prompt(message)?
+        .split_whitespace()
+        .map(|value| value.parse().ok())
+        .collect()
+}
+
+/// Runs the algorithms of `BoxSingleLinkedList` on a list of integers.
+pub fn show_algorithms(options: &Options) {
+    let mut list = BoxSingleLinkedList::new();
+    loop {
+        println!("--- Linked List Algorithms (Box) ---");
+        println!("List: {}", list);
+        println!("1. Enter list");
+        println!("2. Reverse");
+        println!("3. Reverse in groups of k");
+        println!("4. Find middle");
+        println!("5. Remove nth from end");
+        println!("6. Merge with a sorted list");
+        println!("7. Palindrome check");
+        println!("8. Rotate right by k");
+        println!("9. Partition around a pivot");
+        println!("10. Remove duplicates (sorted)");
+        println!("11. Remove duplicates (unsorted)");
+        println!("12. Exit");
+        let Some(choice) = prompt("Enter your choice: ") else {
+            break;
+        };
+
+        // a prompt answered by closing stdin reads as invalid input, the
+        // next prompt then ends the loop
+        match choice.parse::<u8>().unwrap_or(0) {
+            1 => match prompt_values("Enter values separated by spaces: ") {
+                Some(values) => list = values,
+                None => println!("Invalid input. Please enter integers."),
+            },
+            2 => list.reverse(),
+            3 => match prompt("Enter k: ").unwrap_or_default().parse::<usize>() {
+                Ok(k) => list.reverse_groups(k),
+                Err(_) => println!("Invalid input. Please enter a positive integer."),
+            },
+            4 => match list.middle() {
+                Some(value) => println!("Middle: {}", value),
+                None => println!("List is empty."),
+            },
+            5 => match prompt("Enter n: ").unwrap_or_default().parse::<usize>() {
+                Ok(n) => match list.remove_nth_from_end(n) {
+                    Some(value) => println!("Removed {}.", value),
+                    None => println!("Invalid n. Please enter 1 to {}.", list.len()),
+                },
+                Err(_) => println!("Invalid input. Please enter a positive integer."),
+            },
+            6 => match prompt_values("Enter sorted values separated by spaces: ") {
+                Some(other) => {
+                    let sorted = std::mem::replace(&mut list, BoxSingleLinkedList::new());
+                    list = sorted.merge_sorted(other);
+                }
+                None => println!("Invalid input. Please enter integers."),
+            },
+            7 => {
+                if list.is_palindrome() {
+                    println!("The list is a palindrome.");
                 } else {
-                    println!("Invalid input. Please enter an integer.");
+                    println!("The list is not a palindrome.");
                 }
             }
-            5 => {
-                println!("\nLinked List:");
-                list.print();
-            }
-            6 => {
+            8 => match prompt("Enter k: ").unwrap_or_default().parse::<usize>() {
+                Ok(k) => list.rotate(k),
+                Err(_) => println!("Invalid input. Please enter a positive integer."),
+            },
+            9 => match prompt("Enter pivot: ").unwrap_or_default().parse::<i32>() {
+                Ok(pivot) => list.partition(&pivot),
+                Err(_) => println!("Invalid input. Please enter an integer."),
+            },
+            10 => list.dedup_sorted(),
+            11 => list.dedup_unsorted(),
+            12 => {
                 println!("Exiting...");
                 break;
             }
@@ -107,12 +364,9 @@ where
                 println!("Invalid choice. Please enter a valid option.");
             }
         }
-        print!("Press Enter to Continue...");
-        io::stdout().flush().unwrap();
-        io::stdin().read_line(&mut String::new()).unwrap();
-        #[cfg(not(target_os = "windows"))]
-        std::process::Command::new("clear").status().unwrap();
-        #[cfg(target_os = "windows")]
-        std::process::Command::new("cls").status().unwrap();
+        println!("List: {}", list);
+        if wait_and_clear(options).is_none() {
+            break;
+        }
     }
 }
@@ -1,17 +1,354 @@
-use std::io::Write;
-
-use linked_list::{show_list, BoxSingleLinkedList, RcSingleLinkedList};
-
-fn main() {
-    let mut input = String::new();
-    println!("1. Single Linked List (Box)");
-    println!("2. Single Linked List (Rc & RefCell)");
-    print!("Select a Option: ");
-    std::io::stdout().flush().unwrap();
-    std::io::stdin().read_line(&mut input).unwrap();
-    match input.trim() {
-        "1" => show_list::<i32, BoxSingleLinkedList<i32>>(),
-        "2" => show_list::<i32, RcSingleLinkedList<i32>>(),
-        _ => {}
+//! The command line front end, `linked-list --help` explains it.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, IsTerminal},
+    process::ExitCode,
+};
+
+use linked_list::{
+    prompt, run_script, show_algorithms, show_list, ArenaLinkedList, BoxSingleLinkedList,
+    CircularLinkedList, Element, ElementType, Options, RawDoubleLinkedList, RcDoubleLinkedList,
+    RcSingleLinkedList, UnrolledLinkedList,
+};
+
+const INVALID: u8 = 2;
+const IO_ERROR: u8 = 3;
+
+const HELP: &str = "\
+Linked lists to play with, from a menu or a script.
+
+Usage: linked-list [OPTIONS]
+
+Options:
+  --list LIST            the list to use, skips asking for it:
+                           box         single linked list (Box)
+                           rc          single linked list (Rc & RefCell)
+                           rc-double   double linked list (Rc & Weak)
+                           circular    circular linked list
+                           raw-double  double linked list (NonNull)
+                           arena       double linked list (arena)
+                           unrolled    unrolled linked list, 16 values
+                                       to a node
+                         scripts use box if none is given
+  --type TYPE            the element type: i32 (default), i64, f64,
+                         String or char
+  --tolerance TOLERANCE  floats that far apart count as the same value
+                         when removing or updating by value, 0 by default
+  --script FILE          runs the commands in FILE instead of showing the
+                         menu, - reads them from stdin; a script piped
+                         into stdin runs without this option too
+  --no-clear             don't wait for Enter and clear the screen after
+                         every step of the menu
+  -h, --help             shows this help
+
+Operations, as menu entries and script commands:
+  1. Add node            push_front VALUE       adds VALUE in front
+  2. Push Back           push_back VALUE        adds VALUE at the end
+  3. Remove node         remove VALUE           removes the first VALUE
+  4. Update node         update OLD NEW         replaces the first OLD
+  5. Print list          print                  prints the list
+  6. Insert at index     insert_at INDEX VALUE  puts VALUE at INDEX
+  7. Remove at index     remove_at INDEX        removes the element at INDEX
+  8. Get at index        get INDEX              prints the element at INDEX
+  9. Pop front           pop_front              removes the first element
+  10. Pop back           pop_back               removes the last element
+  11. Length             len                    prints the length
+  12. Clear              clear                  removes every element
+  13. Sort list          sort                   sorts the list
+  14. Memory layout      layout                 draws the nodes in memory
+  15. Graphviz DOT       dot                    the same for Graphviz
+  16. Save to file       save FILE [FORMAT]     writes the list to FILE
+  17. Load from file     load FILE [FORMAT]     replaces the list with FILE
+  18. Exit               exit                   stops
+
+FORMAT is text (one value per line), json or csv, the extension of FILE
+picks it if it is left out. JSON and CSV need the serde feature.
+
+A script has one command per line, blank lines and lines starting with #
+are skipped. Values are single words. Addresses in layouts change from
+run to run, the arena shows slots instead. Output goes to stdout, failed
+commands and invalid lines are reported on stderr with their line number.
+
+Exit codes:
+  0  every command ran
+  1  a command failed, like removing a value that isn't there
+  2  an invalid script line or argument, the script stops there
+  3  the script couldn't be read or the output written
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListKind {
+    Box,
+    Rc,
+    RcDouble,
+    Circular,
+    RawDouble,
+    Arena,
+    Unrolled,
+}
+
+impl ListKind {
+    /// Names for `--list` and the menu, in menu order.
+    const ALL: [(Self, &'static str, &'static str); 7] = [
+        (Self::Box, "box", "Single Linked List (Box)"),
+        (Self::Rc, "rc", "Single Linked List (Rc & RefCell)"),
+        (
+            Self::RcDouble,
+            "rc-double",
+            "Double Linked List (Rc & Weak)",
+        ),
+        (Self::Circular, "circular", "Circular Linked List"),
+        (
+            Self::RawDouble,
+            "raw-double",
+            "Double Linked List (NonNull)",
+        ),
+        (Self::Arena, "arena", "Double Linked List (Arena)"),
+        (Self::Unrolled, "unrolled", "Unrolled Linked List"),
+    ];
+
+    fn parse(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|(_, flag, _)| *flag == name)
+            .map(|(kind, _, _)| kind)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|(_, flag, _)| *flag).collect();
+                format!(
+                    "unknown list '{}', expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Calls `$function::<U>($args)` with the `U` that `$ty` stands for.
+macro_rules! with_element_type {
+    ($ty:expr, $function:ident($($arg:expr),*)) => {
+        match $ty {
+            ElementType::I32 => $function::<i32>($($arg),*),
+            ElementType::I64 => $function::<i64>($($arg),*),
+            ElementType::F64 => $function::<f64>($($arg),*),
+            ElementType::String => $function::<String>($($arg),*),
+            ElementType::Char => $function::<char>($($arg),*),
+        }
+    };
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    help: bool,
+    list: Option<ListKind>,
+    ty: Option<ElementType>,
+    tolerance: Option<f64>,
+    script: Option<String>,
+    no_clear: bool,
+}
+
+fn parse_tolerance(input: &str) -> Result<f64, String> {
+    match input.parse::<f64>() {
+        Ok(tolerance) if tolerance >= 0.0 && tolerance.is_finite() => Ok(tolerance),
+        _ => Err(format!(
+            "invalid tolerance '{}', expected a number of at least 0",
+            input
+        )),
+    }
+}
+
+/// Options take their value as the next argument or after `=`.
+fn parse_args(raw: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut raw = raw.into_iter();
+    while let Some(arg) = raw.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_owned())),
+            _ => (arg.as_str(), None),
+        };
+        let takes_value = matches!(name, "--list" | "--type" | "--tolerance" | "--script");
+        if !takes_value && !matches!(name, "-h" | "--help" | "--no-clear") {
+            return Err(format!("unknown argument '{}', see --help", arg));
+        }
+        let value = match (takes_value, inline) {
+            (true, Some(value)) => value,
+            (true, None) => raw
+                .next()
+                .ok_or_else(|| format!("{} needs a value", name))?,
+            (false, Some(_)) => return Err(format!("{} doesn't take a value", name)),
+            (false, None) => String::new(),
+        };
+        match name {
+            "-h" | "--help" => args.help = true,
+            "--list" => args.list = Some(ListKind::parse(&value)?),
+            "--type" => args.ty = Some(value.parse()?),
+            "--tolerance" => args.tolerance = Some(parse_tolerance(&value)?),
+            "--script" => args.script = Some(value),
+            "--no-clear" => args.no_clear = true,
+            _ => unreachable!("unknown arguments are rejected above"),
+        }
+    }
+    Ok(args)
+}
+
+fn run<U: Element>(list: ListKind, script: Box<dyn BufRead>, options: &Options) -> ExitCode {
+    let (out, err) = (&mut io::stdout().lock(), &mut io::stderr().lock());
+    let status = match list {
+        ListKind::Box => run_script::<U, BoxSingleLinkedList<U>>(script, out, err, options),
+        ListKind::Rc => run_script::<U, RcSingleLinkedList<U>>(script, out, err, options),
+        ListKind::RcDouble => run_script::<U, RcDoubleLinkedList<U>>(script, out, err, options),
+        ListKind::Circular => run_script::<U, CircularLinkedList<U>>(script, out, err, options),
+        ListKind::RawDouble => run_script::<U, RawDoubleLinkedList<U>>(script, out, err, options),
+        ListKind::Arena => run_script::<U, ArenaLinkedList<U>>(script, out, err, options),
+        ListKind::Unrolled => run_script::<U, UnrolledLinkedList<U>>(script, out, err, options),
+    };
+    match status {
+        Ok(status) => ExitCode::from(status.code()),
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::from(IO_ERROR)
+        }
+    }
+}
+
+fn show<U: Element>(list: ListKind, options: &Options) {
+    match list {
+        ListKind::Box => show_list::<U, BoxSingleLinkedList<U>>(options),
+        ListKind::Rc => show_list::<U, RcSingleLinkedList<U>>(options),
+        ListKind::RcDouble => show_list::<U, RcDoubleLinkedList<U>>(options),
+        ListKind::Circular => show_list::<U, CircularLinkedList<U>>(options),
+        ListKind::RawDouble => show_list::<U, RawDoubleLinkedList<U>>(options),
+        ListKind::Arena => show_list::<U, ArenaLinkedList<U>>(options),
+        ListKind::Unrolled => show_list::<U, UnrolledLinkedList<U>>(options),
+    }
+}
+
+/// Asks for what the arguments left open, then shows the menu. Stops once
+/// stdin is closed.
+fn interactive(args: &Args, mut options: Options) {
+    let list = match args.list {
+        Some(list) => list,
+        None => {
+            for (number, (_, _, title)) in ListKind::ALL.iter().enumerate() {
+                println!("{}. {}", number + 1, title);
+            }
+            println!("{}. Linked List Algorithms (Box)", ListKind::ALL.len() + 1);
+            let Some(choice) = prompt("Select a Option: ") else {
+                return;
+            };
+            let choice = choice.parse::<usize>().unwrap_or(0);
+            match choice
+                .checked_sub(1)
+                .and_then(|index| ListKind::ALL.get(index))
+            {
+                Some((list, _, _)) => *list,
+                None if choice == ListKind::ALL.len() + 1 => return show_algorithms(&options),
+                None => return,
+            }
+        }
+    };
+    let ty = match args.ty {
+        Some(ty) => ty,
+        None => {
+            let names: Vec<_> = ElementType::ALL.iter().map(|ty| ty.name()).collect();
+            let message = format!("Select an element type ({}) [i32]: ", names.join(", "));
+            loop {
+                let Some(input) = prompt(&message) else {
+                    return;
+                };
+                if input.is_empty() {
+                    break ElementType::default();
+                }
+                match input.parse() {
+                    Ok(ty) => break ty,
+                    Err(message) => eprintln!("{}", message),
+                }
+            }
+        }
+    };
+    if ty == ElementType::F64 && args.tolerance.is_none() {
+        let Some(input) = prompt("Tolerance for matching values [0]: ") else {
+            return;
+        };
+        if !input.is_empty() {
+            match parse_tolerance(&input) {
+                Ok(tolerance) => options.tolerance = tolerance,
+                Err(message) => eprintln!("{}, matching exactly", message),
+            }
+        }
+    }
+    with_element_type!(ty, show(list, &options));
+}
+
+fn main() -> ExitCode {
+    let raw = std::env::args_os().skip(1);
+    let args = match parse_args(raw.map(|arg| arg.to_string_lossy().into_owned())) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(INVALID);
+        }
+    };
+    if args.help {
+        print!("{}", HELP);
+        return ExitCode::SUCCESS;
+    }
+    let options = Options {
+        tolerance: args.tolerance.unwrap_or_default(),
+        clear_screen: !args.no_clear,
+    };
+
+    let script: Box<dyn BufRead> = match args.script.as_deref() {
+        Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(error) => {
+                eprintln!("cannot read {}: {}", path, error);
+                return ExitCode::from(IO_ERROR);
+            }
+        },
+        None if !io::stdin().is_terminal() => Box::new(io::stdin().lock()),
+        None => {
+            interactive(&args, options);
+            return ExitCode::SUCCESS;
+        }
+    };
+    let list = args.list.unwrap_or(ListKind::Box);
+    with_element_type!(args.ty.unwrap_or_default(), run(list, script, &options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, ListKind};
+    use linked_list::ElementType;
+
+    fn parse(args: &[&str]) -> Result<super::Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&["--list", "arena", "--type=f64", "--tolerance", "0.5"]).unwrap();
+        assert_eq!(args.list, Some(ListKind::Arena));
+        assert_eq!(args.ty, Some(ElementType::F64));
+        assert_eq!(args.tolerance, Some(0.5));
+        assert!(!args.no_clear && !args.help && args.script.is_none());
+
+        let args = parse(&["--script=-", "--no-clear", "-h"]).unwrap();
+        assert_eq!(args.script.as_deref(), Some("-"));
+        assert!(args.no_clear && args.help);
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        for (args, message) in [
+            (&["--list", "vec"][..], "unknown list 'vec', expected one of box, rc, rc-double, circular, raw-double, arena, unrolled"),
+            (&["--script"], "--script needs a value"),
+            (&["--no-clear=yes"], "--no-clear doesn't take a value"),
+            (&["script.txt"], "unknown argument 'script.txt', see --help"),
+            (&["--color=auto"], "unknown argument '--color=auto', see --help"),
+        ] {
+            assert_eq!(parse(args).unwrap_err(), message);
+        }
     }
 }
@@ -0,0 +1,254 @@
+//! An immutable cons list. Nothing is ever changed in place: `prepend` and
+//! `tail` return a new list that shares every existing node with the old
+//! one, and the old list stays valid. That is what makes it persistent,
+//! where `BoxSingleLinkedList` overwrites its only version with every
+//! change.
+//!
+//! ```text
+//! let a = List::new().prepend(3).prepend(2);   a: 2 -> 3
+//! let b = a.prepend(1);                        b: 1 -> 2 -> 3
+//! let c = a.tail().prepend(4);                 c: 4 -> 3
+//!
+//!     b: [1] --\
+//!               v
+//!     a: ----> [2] --> [3] --> None
+//!                       ^
+//!     c: [4] -----------/
+//! ```
+//!
+//! The nodes are shared through `Arc`, so a list can be sent to and read
+//! from other threads.
+
+use std::fmt;
+use std::sync::Arc;
+
+struct Node<T> {
+    value: T,
+    next: Option<Arc<Node<T>>>,
+}
+
+pub struct List<T> {
+    head: Option<Arc<Node<T>>>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    pub const fn new() -> Self {
+        Self { head: None, len: 0 }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// A new list with `value` in front of this one. O(1).
+    pub fn prepend(&self, value: T) -> Self {
+        Self {
+            head: Some(Arc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+    /// The first element. O(1).
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+    /// Everything after the first element, the empty list stays empty. O(1).
+    pub fn tail(&self) -> Self {
+        Self {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+            len: self.len.saturating_sub(1),
+        }
+    }
+    /// Whether both lists start at the very same node, and so are equal
+    /// without looking at a single element.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+            len: self.len,
+        }
+    }
+}
+
+// Only the nodes nobody else holds are freed, and one at a time: the
+// default drop would recurse through a long chain and overflow the stack.
+// Unlike `try_unwrap`, `into_inner` lets exactly one of two threads
+// dropping the last two handles at once carry on with the chain.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut current = self.head.take();
+        while let Some(node) = current {
+            match Arc::into_inner(node) {
+                Some(mut node) => current = node.next.take(),
+                // the rest of the chain belongs to another list as well
+                None => break,
+            }
+        }
+    }
+}
+
+impl<T> Clone for List<T> {
+    /// O(1), the clone shares all of its nodes.
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for List<T> {
+    /// Keeps the order of `iter`. A cons list is built from the back, so
+    /// the values are collected first.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        let mut list = Self::new();
+        for value in values.into_iter().rev() {
+            list = list.prepend(value);
+        }
+        list
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && (self.ptr_eq(other) || self.iter().eq(other))
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for value in self {
+            write!(f, "{} -> ", value)?;
+        }
+        write!(f, "None")
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        self.len -= 1;
+        Some(&node.value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::List;
+
+    fn values(list: &List<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_prepend_head_tail() {
+        let empty = List::new();
+        assert_eq!(empty.head(), None);
+        assert!(empty.tail().is_empty());
+        let a = empty.prepend(3).prepend(2);
+        let b = a.prepend(1);
+        let c = a.tail().prepend(4);
+        // every version is still intact
+        assert_eq!(values(&a), vec![2, 3]);
+        assert_eq!(values(&b), vec![1, 2, 3]);
+        assert_eq!(values(&c), vec![4, 3]);
+        assert!(empty.is_empty());
+        assert_eq!(b.head(), Some(&1));
+        assert_eq!(b.len(), 3);
+        assert_eq!(b.to_string(), "1 -> 2 -> 3 -> None");
+    }
+
+    #[test]
+    fn test_structural_sharing() {
+        let a: List<i32> = (1..=3).collect();
+        let b = a.prepend(0);
+        assert!(b.tail().ptr_eq(&a));
+        assert!(a.tail().ptr_eq(&b.tail().tail()));
+        assert!(!a.ptr_eq(&b));
+        let copy: List<i32> = (1..=3).collect();
+        assert_eq!(a, copy);
+        assert!(!a.ptr_eq(&copy));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<List<i32>>();
+
+        let shared: List<i32> = (0..100).collect();
+        let sums: Vec<i32> = (0..4)
+            .map(|i| {
+                let list = shared.prepend(i);
+                thread::spawn(move || list.iter().sum::<i32>())
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        assert_eq!(sums, vec![4950, 4951, 4952, 4953]);
+        assert_eq!(shared.len(), 100);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_drop_long_shared_chain() {
+        let mut long = List::new();
+        for i in 0..1_000_000 {
+            long = long.prepend(i);
+        }
+        let branch = long.tail().prepend(-1);
+        // the first drop stops where `branch` shares the chain, the second
+        // one walks the whole chain
+        drop(long);
+        assert_eq!(branch.len(), 1_000_000);
+        drop(branch);
+    }
+}
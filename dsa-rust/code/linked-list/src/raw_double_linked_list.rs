@@ -0,0 +1,1000 @@
+//! A doubly linked list built on raw `NonNull` pointers.
+//!
+//! The `Box` and `Rc` lists let the compiler check every link, this one
+//! manages the links by hand, the same way `std::collections::LinkedList`
+//! does. In exchange for the `unsafe` code there is no reference counting
+//! and no runtime borrow checking, and whole lists can be spliced in O(1).
+//!
+//! Invariants kept by every method:
+//!
+//! - every node is allocated with `Box` and owned by exactly one list
+//! - `head.prev` and `tail.next` are `None`
+//! - for every node, `node.next.prev == node` and `node.prev.next == node`
+//! - `len` is the number of nodes reachable from `head`
+
+mod big_uint;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+use crate::{Layout, LinkedListT, Location, NodeLayout, Pointer, PointerKind, Root};
+
+pub use big_uint::BigUint;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+/// A detached run of nodes: first node, last node and how many there are.
+type Chain<T> = (NonNull<Node<T>>, NonNull<Node<T>>, usize);
+
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+    prev: Link<T>,
+}
+
+pub struct LinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    // tells the drop checker that we own `Box<Node<T>>`s
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+// SAFETY: the list owns its nodes just like a `Vec` owns its elements.
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
+impl<T> LinkedList<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: nodes stay alive as long as the list is borrowed.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: see `front`.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: `&mut self` makes this the only reference into the list.
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: see `front_mut`.
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+    fn new_node(value: T) -> NonNull<Node<T>> {
+        let node = Box::new(Node {
+            value,
+            next: None,
+            prev: None,
+        });
+        NonNull::from(Box::leak(node))
+    }
+    /// Links the chain `start..=end` of `len` nodes between `prev` and
+    /// `next`, which have to be neighbours in this list (`None` meaning the
+    /// respective end of the list).
+    ///
+    /// # Safety
+    ///
+    /// `start..=end` must be a valid chain not owned by any list.
+    unsafe fn link_between(
+        &mut self,
+        prev: Link<T>,
+        next: Link<T>,
+        start: NonNull<Node<T>>,
+        end: NonNull<Node<T>>,
+        len: usize,
+    ) {
+        unsafe {
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(start),
+                None => self.head = Some(start),
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(end),
+                None => self.tail = Some(end),
+            }
+            (*start.as_ptr()).prev = prev;
+            (*end.as_ptr()).next = next;
+        }
+        self.len += len;
+    }
+    /// Unlinks `node` and gives back ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `node` must belong to this list.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> Box<Node<T>> {
+        unsafe {
+            let node = Box::from_raw(node.as_ptr());
+            match node.prev {
+                Some(prev) => (*prev.as_ptr()).next = node.next,
+                None => self.head = node.next,
+            }
+            match node.next {
+                Some(next) => (*next.as_ptr()).prev = node.prev,
+                None => self.tail = node.prev,
+            }
+            self.len -= 1;
+            node
+        }
+    }
+    /// Takes every node out of the list as one chain.
+    fn detach(&mut self) -> Option<Chain<T>> {
+        let start = self.head.take()?;
+        let end = self.tail.take().unwrap();
+        Some((start, end, mem::replace(&mut self.len, 0)))
+    }
+    pub fn push_front(&mut self, value: T) {
+        let node = Self::new_node(value);
+        // SAFETY: a fresh node is a valid one element chain.
+        unsafe { self.link_between(None, self.head, node, node, 1) }
+    }
+    pub fn push_back(&mut self, value: T) {
+        let node = Self::new_node(value);
+        // SAFETY: see `push_front`.
+        unsafe { self.link_between(self.tail, None, node, node, 1) }
+    }
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: `head` belongs to this list.
+        self.head.map(|node| unsafe { self.unlink(node).value })
+    }
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: `tail` belongs to this list.
+        self.tail.map(|node| unsafe { self.unlink(node).value })
+    }
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+    /// Moves every element of `other` to the end of `self` in O(1).
+    pub fn append(&mut self, other: &mut Self) {
+        if let Some((start, end, len)) = other.detach() {
+            // SAFETY: the chain was just detached from `other`.
+            unsafe { self.link_between(self.tail, None, start, end, len) }
+        }
+    }
+    /// Splits the list in two at `at`, returning everything from index
+    /// `at` onwards. O(min(at, len - at)).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "cannot split off at a nonexistent index");
+        if at == 0 {
+            return mem::take(self);
+        }
+        if at == self.len {
+            return Self::new();
+        }
+        let mut cursor = self.cursor_at_mut(at - 1);
+        cursor.split_after()
+    }
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+    /// A cursor on the first element, or on the "ghost" if the list is empty.
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        Cursor {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+    /// A cursor on the element at `index`, walking from the nearer end.
+    /// `index == len` gives the ghost.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn cursor_at_mut(&mut self, index: usize) -> CursorMut<'_, T> {
+        assert!(index <= self.len, "index {} out of bounds", index);
+        if index <= self.len / 2 {
+            let mut cursor = self.cursor_front_mut();
+            for _ in 0..index {
+                cursor.move_next();
+            }
+            cursor
+        } else {
+            // starting on the ghost, `len - index` steps back reach `index`
+            let len = self.len;
+            let mut cursor = CursorMut {
+                current: None,
+                index: len,
+                list: self,
+            };
+            for _ in index..len {
+                cursor.move_prev();
+            }
+            cursor
+        }
+    }
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Popping one node at a time keeps the drop iterative, no matter how long
+// the list is.
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+/// A read only position in a [`LinkedList`].
+///
+/// Besides the elements the cursor can point at a "ghost" position that
+/// sits between the tail and the head, so moving past either end wraps
+/// around through it.
+pub struct Cursor<'a, T> {
+    list: &'a LinkedList<T>,
+    current: Link<T>,
+    // index of `current`, `list.len` while on the ghost
+    index: usize,
+}
+
+impl<'a, T> Cursor<'a, T> {
+    /// `None` while on the ghost.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+    pub fn move_next(&mut self) {
+        match self.current {
+            // SAFETY: `current` belongs to the borrowed list.
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).next;
+                self.index += 1;
+            },
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+        }
+    }
+    pub fn move_prev(&mut self) {
+        match self.current {
+            // SAFETY: `current` belongs to the borrowed list.
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).prev;
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            },
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+    pub fn current(&self) -> Option<&'a T> {
+        // SAFETY: `current` belongs to the list, which is borrowed for `'a`.
+        self.current.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+    pub fn peek_next(&self) -> Option<&'a T> {
+        // SAFETY: see `current`.
+        unsafe {
+            let next = match self.current {
+                Some(node) => (*node.as_ptr()).next,
+                None => self.list.head,
+            };
+            next.map(|node| &(*node.as_ptr()).value)
+        }
+    }
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        // SAFETY: see `current`.
+        unsafe {
+            let prev = match self.current {
+                Some(node) => (*node.as_ptr()).prev,
+                None => self.list.tail,
+            };
+            prev.map(|node| &(*node.as_ptr()).value)
+        }
+    }
+}
+
+/// A position in a [`LinkedList`] that can also edit the list around it.
+///
+/// Uses the same "ghost" position as [`Cursor`].
+pub struct CursorMut<'a, T> {
+    list: &'a mut LinkedList<T>,
+    current: Link<T>,
+    index: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+    pub fn move_next(&mut self) {
+        match self.current {
+            // SAFETY: `current` belongs to the borrowed list.
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).next;
+                self.index += 1;
+            },
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+        }
+    }
+    pub fn move_prev(&mut self) {
+        match self.current {
+            // SAFETY: `current` belongs to the borrowed list.
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).prev;
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            },
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: the cursor holds the only borrow of the list.
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        // SAFETY: see `current`.
+        unsafe {
+            let next = match self.current {
+                Some(node) => (*node.as_ptr()).next,
+                None => self.list.head,
+            };
+            next.map(|node| &mut (*node.as_ptr()).value)
+        }
+    }
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        // SAFETY: see `current`.
+        unsafe {
+            let prev = match self.current {
+                Some(node) => (*node.as_ptr()).prev,
+                None => self.list.tail,
+            };
+            prev.map(|node| &mut (*node.as_ptr()).value)
+        }
+    }
+    /// The nodes on either side of the cursor, the ghost sitting between
+    /// the tail and the head.
+    fn neighbours(&self) -> (Link<T>, Link<T>) {
+        match self.current {
+            // SAFETY: `current` belongs to the borrowed list.
+            Some(node) => unsafe { ((*node.as_ptr()).prev, (*node.as_ptr()).next) },
+            None => (self.list.tail, self.list.head),
+        }
+    }
+    /// Inserts after the current element, or at the front on the ghost.
+    pub fn insert_after(&mut self, value: T) {
+        let node = LinkedList::new_node(value);
+        let next = self.neighbours().1;
+        // SAFETY: a fresh node is a valid chain, `current` and `next`
+        // are neighbours.
+        unsafe { self.list.link_between(self.current, next, node, node, 1) }
+        if self.current.is_none() {
+            self.index += 1;
+        }
+    }
+    /// Inserts before the current element, or at the back on the ghost.
+    pub fn insert_before(&mut self, value: T) {
+        let node = LinkedList::new_node(value);
+        let prev = self.neighbours().0;
+        // SAFETY: see `insert_after`.
+        unsafe { self.list.link_between(prev, self.current, node, node, 1) }
+        self.index += 1;
+    }
+    /// Removes the current element and moves on to the next one.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // SAFETY: `current` belongs to the borrowed list.
+        unsafe {
+            self.current = (*node.as_ptr()).next;
+            Some(self.list.unlink(node).value)
+        }
+    }
+    /// Moves all of `other` after the current element (at the front on
+    /// the ghost) in O(1).
+    pub fn splice_after(&mut self, mut other: LinkedList<T>) {
+        let Some((start, end, len)) = other.detach() else {
+            return;
+        };
+        let next = self.neighbours().1;
+        // SAFETY: the chain was just detached from `other`.
+        unsafe { self.list.link_between(self.current, next, start, end, len) }
+        if self.current.is_none() {
+            self.index += len;
+        }
+    }
+    /// Moves all of `other` before the current element (at the back on
+    /// the ghost) in O(1).
+    pub fn splice_before(&mut self, mut other: LinkedList<T>) {
+        let Some((start, end, len)) = other.detach() else {
+            return;
+        };
+        let prev = self.neighbours().0;
+        // SAFETY: see `splice_after`.
+        unsafe { self.list.link_between(prev, self.current, start, end, len) }
+        self.index += len;
+    }
+    /// Splits off everything after the current element (the whole list on
+    /// the ghost) in O(1).
+    pub fn split_after(&mut self) -> LinkedList<T> {
+        let Some(node) = self.current else {
+            self.index = 0;
+            return mem::take(self.list);
+        };
+        let mut rest = LinkedList::new();
+        // SAFETY: `node` belongs to the borrowed list, the nodes after it
+        // are moved to `rest` as a whole chain.
+        unsafe {
+            if let Some(next) = (*node.as_ptr()).next.take() {
+                (*next.as_ptr()).prev = None;
+                rest.head = Some(next);
+                rest.tail = self.list.tail;
+                rest.len = self.list.len - self.index - 1;
+                self.list.tail = Some(node);
+                self.list.len = self.index + 1;
+            }
+        }
+        rest
+    }
+    /// Splits off everything before the current element (the whole list on
+    /// the ghost) in O(1).
+    pub fn split_before(&mut self) -> LinkedList<T> {
+        let Some(node) = self.current else {
+            self.index = 0;
+            return mem::take(self.list);
+        };
+        let mut front = LinkedList::new();
+        // SAFETY: see `split_after`.
+        unsafe {
+            if let Some(prev) = (*node.as_ptr()).prev.take() {
+                (*prev.as_ptr()).next = None;
+                front.head = self.list.head;
+                front.tail = Some(prev);
+                front.len = self.index;
+                self.list.head = Some(node);
+                self.list.len -= self.index;
+                self.index = 0;
+            }
+        }
+        front
+    }
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self.list,
+            current: self.current,
+            index: self.index,
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `len` keeps `head` inside the borrowed list.
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &(*node.as_ptr()).value
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `len` keeps `tail` inside the borrowed list.
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &(*node.as_ptr()).value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: `len` keeps `head` and `tail` from crossing, so every
+        // node is handed out at most once.
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).value
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: see `next`.
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "None <-> ")?;
+        for value in self.iter() {
+            write!(f, "{} <-> ", value)?;
+        }
+        write!(f, "None")
+    }
+}
+
+impl<T: fmt::Display + PartialEq> LinkedListT for LinkedList<T> {
+    type Item = T;
+    fn new() -> Self {
+        Self::new()
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn contains(&self, value: &Self::Item) -> bool {
+        self.iter().any(|v| v == value)
+    }
+    fn get(&self, index: usize) -> Option<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        if index >= self.len {
+            return None;
+        }
+        if index <= self.len / 2 {
+            self.iter().nth(index).cloned()
+        } else {
+            self.iter().rev().nth(self.len - index - 1).cloned()
+        }
+    }
+    fn to_vec(&self) -> Vec<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.iter().cloned().collect()
+    }
+    fn push_front(&mut self, value: Self::Item) {
+        self.push_front(value);
+    }
+    fn push_back(&mut self, value: Self::Item) {
+        self.push_back(value);
+    }
+    fn pop_front(&mut self) -> Option<Self::Item> {
+        self.pop_front()
+    }
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        self.pop_back()
+    }
+    fn insert_at(&mut self, index: usize, value: Self::Item) {
+        self.cursor_at_mut(index).insert_before(value);
+    }
+    fn remove_at(&mut self, index: usize) -> Option<Self::Item> {
+        if index >= self.len {
+            return None;
+        }
+        self.cursor_at_mut(index).remove_current()
+    }
+    fn remove(&mut self, value: &Self::Item) -> bool {
+        let mut cursor = self.cursor_front_mut();
+        while let Some(current) = cursor.current() {
+            if *current == *value {
+                cursor.remove_current();
+                return true;
+            }
+            cursor.move_next();
+        }
+        false
+    }
+    fn update(&mut self, old_val: &Self::Item, new_val: Self::Item) -> Option<Self::Item> {
+        let value = self.iter_mut().find(|v| **v == *old_val)?;
+        Some(mem::replace(value, new_val))
+    }
+    fn clear(&mut self) {
+        self.clear();
+    }
+    fn layout(&self) -> Layout {
+        let pointer = |link: Link<T>| {
+            link.map(|node| {
+                Pointer::new(
+                    PointerKind::NonNull,
+                    Location::Address(node.as_ptr() as usize),
+                )
+            })
+        };
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut current = self.head;
+        while let Some(node) = current {
+            // SAFETY: every linked node is alive while the list is borrowed
+            let node = unsafe { node.as_ref() };
+            let mut layout = NodeLayout::new(
+                Location::Address(node as *const Node<T> as usize),
+                &node.value,
+            );
+            layout.next = pointer(node.next);
+            layout.prev = pointer(node.prev);
+            nodes.push(layout);
+            current = node.next;
+        }
+        Layout {
+            roots: vec![
+                Root {
+                    name: "head",
+                    link: pointer(self.head),
+                },
+                Root {
+                    name: "tail",
+                    link: pointer(self.tail),
+                },
+            ],
+            nodes,
+        }
+    }
+    fn header(&self) -> &str {
+        "--- Double Linked List (NonNull) CLI ---"
+    }
+}
+
+#[cfg(test)]
+impl<T: fmt::Display + PartialEq + Clone> crate::tests::Snapshot for LinkedList<T> {
+    fn snapshot(&self) -> Vec<T> {
+        let values: Vec<T> = self.iter().cloned().collect();
+        let mut reversed: Vec<T> = self.iter().rev().cloned().collect();
+        reversed.reverse();
+        assert!(values == reversed, "prev links are out of sync");
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+
+    fn list(values: &[i32]) -> LinkedList<i32> {
+        let mut list = LinkedList::new();
+        for &v in values {
+            list.push_back(v);
+        }
+        list
+    }
+
+    fn check(list: &LinkedList<i32>, expected: &[i32]) {
+        assert_eq!(list.len(), expected.len());
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+        let mut reversed = expected.to_vec();
+        reversed.reverse();
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), reversed);
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut l = LinkedList::new();
+        assert_eq!(l.pop_front(), None);
+        l.push_back(2);
+        l.push_front(1);
+        l.push_back(3);
+        check(&l, &[1, 2, 3]);
+        assert_eq!(l.front(), Some(&1));
+        assert_eq!(l.back(), Some(&3));
+        *l.front_mut().unwrap() = 10;
+        assert_eq!(l.pop_front(), Some(10));
+        assert_eq!(l.pop_back(), Some(3));
+        assert_eq!(l.pop_back(), Some(2));
+        assert_eq!(l.pop_back(), None);
+        check(&l, &[]);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut l = list(&[1, 2, 3, 4]);
+        for v in l.iter_mut() {
+            *v *= 10;
+        }
+        let mut iter = l.iter_mut();
+        assert_eq!(iter.next(), Some(&mut 10));
+        assert_eq!(iter.next_back(), Some(&mut 40));
+        assert_eq!(iter.len(), 2);
+        check(&l, &[10, 20, 30, 40]);
+        assert_eq!(
+            l.into_iter().rev().collect::<Vec<_>>(),
+            vec![40, 30, 20, 10]
+        );
+        // dropping a half consumed `IntoIter` frees the rest
+        let mut iter = list(&[1, 2, 3]).into_iter();
+        assert_eq!(iter.next(), Some(1));
+    }
+
+    #[test]
+    fn test_cursor_moves() {
+        let l = list(&[1, 2, 3]);
+        let mut c = l.cursor_front();
+        assert_eq!((c.current(), c.index()), (Some(&1), Some(0)));
+        c.move_prev();
+        assert_eq!((c.current(), c.index()), (None, None));
+        assert_eq!(c.peek_next(), Some(&1));
+        assert_eq!(c.peek_prev(), Some(&3));
+        c.move_prev();
+        assert_eq!((c.current(), c.index()), (Some(&3), Some(2)));
+        c.move_next();
+        c.move_next();
+        assert_eq!((c.current(), c.index()), (Some(&1), Some(0)));
+        let c = l.cursor_back();
+        assert_eq!((c.current(), c.index()), (Some(&3), Some(2)));
+        let empty = LinkedList::<i32>::new();
+        assert_eq!(empty.cursor_front().current(), None);
+    }
+
+    #[test]
+    fn test_cursor_insert_remove() {
+        let mut l = list(&[1, 3]);
+        let mut c = l.cursor_front_mut();
+        c.insert_after(2);
+        c.insert_before(0);
+        assert_eq!(c.index(), Some(1));
+        assert_eq!(c.current(), Some(&mut 1));
+        c.move_next();
+        c.move_next();
+        c.move_next();
+        // on the ghost
+        c.insert_after(-1);
+        c.insert_before(4);
+        assert_eq!(c.index(), None);
+        c.move_next();
+        assert_eq!(c.index(), Some(0));
+        check(&l, &[-1, 0, 1, 2, 3, 4]);
+
+        let mut c = l.cursor_front_mut();
+        c.move_next();
+        assert_eq!(c.remove_current(), Some(0));
+        assert_eq!((c.current().copied(), c.index()), (Some(1), Some(1)));
+        c.move_prev();
+        assert_eq!(c.remove_current(), Some(-1));
+        let mut c = l.cursor_back_mut();
+        assert_eq!(c.remove_current(), Some(4));
+        assert_eq!(c.remove_current(), None);
+        c.move_next();
+        assert_eq!(c.current(), Some(&mut 1));
+        check(&l, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_cursor_splice() {
+        let mut l = list(&[1, 4]);
+        let mut c = l.cursor_front_mut();
+        c.splice_after(list(&[2, 3]));
+        assert_eq!(c.index(), Some(0));
+        c.splice_before(list(&[-1, 0]));
+        assert_eq!(c.index(), Some(2));
+        c.splice_before(LinkedList::new());
+        c.move_prev();
+        c.move_prev();
+        c.move_prev();
+        // on the ghost: after means the front, before means the back
+        c.splice_after(list(&[-3, -2]));
+        c.splice_before(list(&[5, 6]));
+        check(&l, &[-3, -2, -1, 0, 1, 2, 3, 4, 5, 6]);
+        let mut c = l.cursor_front_mut();
+        c.move_next();
+        assert_eq!(c.index(), Some(1));
+    }
+
+    #[test]
+    fn test_cursor_split() {
+        let mut l = list(&[1, 2, 3, 4, 5]);
+        let mut c = l.cursor_front_mut();
+        c.move_next();
+        c.move_next();
+        let back = c.split_after();
+        let front = c.split_before();
+        assert_eq!(c.index(), Some(0));
+        check(&back, &[4, 5]);
+        check(&front, &[1, 2]);
+        check(&l, &[3]);
+        let mut c = l.cursor_front_mut();
+        c.move_next();
+        check(&c.split_after(), &[3]);
+        check(&l, &[]);
+    }
+
+    #[test]
+    fn test_split_off_append() {
+        for at in 0..=5 {
+            let mut l = list(&[0, 1, 2, 3, 4]);
+            let tail = l.split_off(at);
+            let expected: Vec<i32> = (0..5).collect();
+            check(&l, &expected[..at]);
+            check(&tail, &expected[at..]);
+            let mut tail = tail;
+            l.append(&mut tail);
+            check(&l, &expected);
+            check(&tail, &[]);
+        }
+        let mut empty = LinkedList::new();
+        empty.append(&mut list(&[1]));
+        check(&empty, &[1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_off_out_of_bounds() {
+        list(&[1]).split_off(2);
+    }
+
+    #[test]
+    fn test_drops_values() {
+        use std::rc::Rc;
+        let value = Rc::new(());
+        let mut l = LinkedList::new();
+        for _ in 0..10 {
+            l.push_back(Rc::clone(&value));
+        }
+        let tail = l.split_off(4);
+        drop(l);
+        assert_eq!(Rc::strong_count(&value), 7);
+        drop(tail);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,320 @@
+//! Non-negative integers of any size, kept as a list of decimal digits.
+//!
+//! The least significant digit is at the front, which is where adding,
+//! subtracting and multiplying by hand start, and carries move towards the
+//! back. Printing and comparing start at the most significant digit, so
+//! the list is walked backwards for those, which is why this is built on
+//! the doubly linked list.
+//!
+//! There are no zeros at the back of the list: `0` is the empty list and
+//! every number has exactly one representation.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+use super::LinkedList;
+
+#[derive(Clone, Default)]
+pub struct BigUint {
+    /// Decimal digits, the least significant first.
+    digits: LinkedList<u8>,
+}
+
+impl BigUint {
+    /// Zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+    /// How many decimal digits the number has, 1 for zero.
+    pub fn digit_count(&self) -> usize {
+        self.digits.len().max(1)
+    }
+    /// Drops the zeros the back may have after a subtraction.
+    fn normalize(mut self) -> Self {
+        while self.digits.back() == Some(&0) {
+            self.digits.pop_back();
+        }
+        self
+    }
+    /// `self - other`, `None` if `other` is larger. O(n).
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if *self < *other {
+            return None;
+        }
+        let mut right = other.digits.iter();
+        let mut borrow = 0;
+        let digits = self.digits.iter().map(|&digit| {
+            let subtrahend = right.next().copied().unwrap_or(0) + borrow;
+            if digit >= subtrahend {
+                borrow = 0;
+                digit - subtrahend
+            } else {
+                borrow = 1;
+                digit + 10 - subtrahend
+            }
+        });
+        Some(
+            Self {
+                digits: digits.collect(),
+            }
+            .normalize(),
+        )
+    }
+    /// Long multiplication: one row for each digit of `self`, shifted by
+    /// its position, all added up. O(n·(n + m)).
+    fn product(&self, other: &Self) -> Self {
+        let mut product = Self::new();
+        if other.is_zero() {
+            return product;
+        }
+        for (shift, &digit) in self.digits.iter().enumerate() {
+            if digit != 0 {
+                product = &product + &other.mul_digit(digit, shift);
+            }
+        }
+        product
+    }
+    /// `self·digit·10^shift`, one row of long multiplication.
+    fn mul_digit(&self, digit: u8, shift: usize) -> Self {
+        let mut digits: LinkedList<u8> = (0..shift).map(|_| 0).collect();
+        let mut carry = 0;
+        for &d in &self.digits {
+            let product = d * digit + carry;
+            digits.push_back(product % 10);
+            carry = product / 10;
+        }
+        if carry > 0 {
+            digits.push_back(carry);
+        }
+        Self { digits }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(mut n: u64) -> Self {
+        let mut digits = LinkedList::new();
+        while n > 0 {
+            digits.push_back((n % 10) as u8);
+            n /= 10;
+        }
+        Self { digits }
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = String;
+
+    /// Reads decimal digits, leading zeros are allowed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("empty number".to_owned());
+        }
+        let mut digits = LinkedList::new();
+        for c in s.chars() {
+            let digit = c
+                .to_digit(10)
+                .ok_or_else(|| format!("invalid digit '{}' in '{}'", c, s))?;
+            digits.push_front(digit as u8);
+        }
+        Ok(Self { digits }.normalize())
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad("0");
+        }
+        let digits: String = self
+            .digits
+            .iter()
+            .rev()
+            .map(|&digit| char::from(b'0' + digit))
+            .collect();
+        f.pad(&digits)
+    }
+}
+
+impl fmt::Debug for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BigUint({})", self)
+    }
+}
+
+impl PartialEq for BigUint {
+    fn eq(&self, other: &Self) -> bool {
+        self.digits.len() == other.digits.len() && self.digits.iter().eq(&other.digits)
+    }
+}
+
+impl Eq for BigUint {}
+
+impl Ord for BigUint {
+    /// More digits is larger, otherwise the first digit that differs from
+    /// the most significant end decides.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.digits
+            .len()
+            .cmp(&other.digits.len())
+            .then_with(|| self.digits.iter().rev().cmp(other.digits.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    /// Adds digit by digit from the least significant one, carrying into
+    /// the next. O(max(n, m)).
+    fn add(self, other: &BigUint) -> BigUint {
+        let (mut left, mut right) = (self.digits.iter(), other.digits.iter());
+        let mut digits = LinkedList::new();
+        let mut carry = 0;
+        loop {
+            let (l, r) = (left.next(), right.next());
+            if l.is_none() && r.is_none() {
+                break;
+            }
+            let sum = l.copied().unwrap_or(0) + r.copied().unwrap_or(0) + carry;
+            digits.push_back(sum % 10);
+            carry = sum / 10;
+        }
+        if carry > 0 {
+            digits.push_back(carry);
+        }
+        BigUint { digits }
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+    fn add(self, other: BigUint) -> BigUint {
+        &self + &other
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    /// # Panics
+    ///
+    /// Panics if `other` is larger, see [`BigUint::checked_sub`].
+    fn sub(self, other: &BigUint) -> BigUint {
+        self.checked_sub(other)
+            .unwrap_or_else(|| panic!("cannot subtract {} from {}", other, self))
+    }
+}
+
+impl Sub for BigUint {
+    type Output = BigUint;
+    fn sub(self, other: BigUint) -> BigUint {
+        &self - &other
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+    fn mul(self, other: &BigUint) -> BigUint {
+        self.product(other)
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+    fn mul(self, other: BigUint) -> BigUint {
+        &self * &other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigUint;
+
+    fn big(s: &str) -> BigUint {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_display() {
+        assert_eq!(big("12345").to_string(), "12345");
+        assert_eq!(big("007").to_string(), "7");
+        assert_eq!(big("000"), BigUint::new());
+        assert_eq!(BigUint::new().to_string(), "0");
+        assert_eq!(BigUint::new().digit_count(), 1);
+        assert_eq!(big("98765432109876543210").digit_count(), 20);
+        assert_eq!(BigUint::from(u64::MAX).to_string(), u64::MAX.to_string());
+        assert_eq!(format!("{:>5}", big("42")), "   42");
+        assert_eq!(format!("{:?}", big("42")), "BigUint(42)");
+        assert_eq!("".parse::<BigUint>(), Err("empty number".into()));
+        assert_eq!(
+            "12a".parse::<BigUint>(),
+            Err("invalid digit 'a' in '12a'".into())
+        );
+        assert!("-1".parse::<BigUint>().is_err());
+    }
+
+    #[test]
+    fn test_compare() {
+        assert!(big("100") > big("99"));
+        assert!(big("123") < big("132"));
+        assert!(BigUint::new() < big("1"));
+        assert_eq!(big("0042"), big("42"));
+    }
+
+    #[test]
+    fn test_add_sub() {
+        assert_eq!(big("999") + big("1"), big("1000"));
+        assert_eq!(
+            (&big("18446744073709551615") + &big("18446744073709551615")).to_string(),
+            "36893488147419103230"
+        );
+        assert_eq!(big("1000") - big("1"), big("999"));
+        assert_eq!(big("1000") - big("1000"), BigUint::new());
+        assert_eq!(big("5").checked_sub(&big("6")), None);
+        // everything a u128 can check
+        let values = [0u128, 1, 9, 10, 99, 12_345, 1 << 64, u64::MAX as u128 * 3];
+        for a in values {
+            for b in values {
+                let (x, y) = (big(&a.to_string()), big(&b.to_string()));
+                assert_eq!((&x + &y).to_string(), (a + b).to_string());
+                assert_eq!(
+                    x.checked_sub(&y).map(|d| d.to_string()),
+                    a.checked_sub(b).map(|d| d.to_string())
+                );
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot subtract 6 from 5")]
+    fn test_sub_underflow() {
+        let _ = big("5") - big("6");
+    }
+
+    #[test]
+    fn test_mul() {
+        assert_eq!(big("12") * big("34"), big("408"));
+        assert_eq!(big("0") * big("34"), BigUint::new());
+        assert_eq!(big("34") * big("0"), BigUint::new());
+        let factorial = (1..=30).fold(BigUint::from(1), |product, n| product * BigUint::from(n));
+        assert_eq!(factorial.to_string(), "265252859812191058636308480000000");
+        let two = BigUint::from(2);
+        let power = (0..100).fold(BigUint::from(1), |power, _| &power * &two);
+        assert_eq!(power.to_string(), "1267650600228229401496703205376");
+        for (a, b) in [(u64::MAX, u64::MAX), (1 << 40, 12_345), (99, 101)] {
+            let product = BigUint::from(a) * BigUint::from(b);
+            assert_eq!(product.to_string(), (a as u128 * b as u128).to_string());
+        }
+    }
+}
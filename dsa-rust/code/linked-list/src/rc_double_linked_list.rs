@@ -0,0 +1,377 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
+
+use crate::{Layout, LinkedListT, Location, NodeLayout, Pointer, PointerKind, RcCounts, Root};
+
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+
+// `next` owns the following node, `prev` only points back with a `Weak`,
+// otherwise every pair of neighbours would be a reference cycle.
+struct Node<T: fmt::Display + PartialEq> {
+    value: T,
+    next: Link<T>,
+    prev: Option<Weak<RefCell<Node<T>>>>,
+}
+
+impl<T: fmt::Display + PartialEq> Node<T> {
+    fn new(value: T) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            value,
+            next: None,
+            prev: None,
+        }))
+    }
+}
+
+pub struct LinkedList<T: fmt::Display + PartialEq> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+}
+
+// Cutting every `next` link first keeps the drop of a long list from
+// recursing through all of its nodes.
+impl<T: fmt::Display + PartialEq> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: fmt::Display + PartialEq> LinkedList<T> {
+    /// Unlinks `node` from its neighbours and hands it back.
+    fn unlink(&mut self, node: Rc<RefCell<Node<T>>>) -> Rc<RefCell<Node<T>>> {
+        let next = node.borrow_mut().next.take();
+        let prev = node.borrow_mut().prev.take().and_then(|p| p.upgrade());
+        match &next {
+            Some(next) => next.borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev.clone(),
+        }
+        match prev {
+            Some(prev) => prev.borrow_mut().next = next,
+            None => self.head = next,
+        }
+        self.len -= 1;
+        node
+    }
+    /// Unwraps a node that is no longer linked from anywhere.
+    fn into_value(node: Rc<RefCell<Node<T>>>) -> T {
+        match Rc::try_unwrap(node) {
+            Ok(node) => node.into_inner().value,
+            Err(_) => unreachable!("unlinked node is still shared"),
+        }
+    }
+    /// Walks from whichever end is closer to `index`.
+    fn node_at(&self, index: usize) -> Link<T> {
+        if index >= self.len {
+            return None;
+        }
+        if index <= self.len / 2 {
+            let mut current = self.head.clone();
+            for _ in 0..index {
+                current = current?.borrow().next.clone();
+            }
+            current
+        } else {
+            let mut current = self.tail.clone();
+            for _ in index + 1..self.len {
+                current = current?.borrow().prev.as_ref().and_then(Weak::upgrade);
+            }
+            current
+        }
+    }
+    fn find(&self, value: &T) -> Link<T> {
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            if node.borrow().value == *value {
+                return Some(node);
+            }
+            current = node.borrow().next.clone();
+        }
+        None
+    }
+    /// Iterates over clones of the values, use `.rev()` to walk from the tail.
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: Clone,
+    {
+        Iter {
+            front: self.head.clone(),
+            back: self.tail.clone(),
+            len: self.len,
+            _list: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Display + PartialEq> fmt::Display for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut current = self.head.clone();
+        write!(f, "None <-> ")?;
+        while let Some(node) = current {
+            write!(f, "{} <-> ", node.borrow().value)?;
+            current = node.borrow().next.clone();
+        }
+        write!(f, "None")
+    }
+}
+
+impl<T: fmt::Display + PartialEq> LinkedListT for LinkedList<T> {
+    type Item = T;
+    fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn contains(&self, value: &Self::Item) -> bool {
+        self.find(value).is_some()
+    }
+    fn get(&self, index: usize) -> Option<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.node_at(index).map(|node| node.borrow().value.clone())
+    }
+    fn to_vec(&self) -> Vec<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.iter().collect()
+    }
+    fn push_front(&mut self, value: Self::Item) {
+        let new_node = Node::new(value);
+        match self.head.take() {
+            Some(old_head) => {
+                old_head.borrow_mut().prev = Some(Rc::downgrade(&new_node));
+                new_node.borrow_mut().next = Some(old_head);
+            }
+            None => self.tail = Some(Rc::clone(&new_node)),
+        }
+        self.head = Some(new_node);
+        self.len += 1;
+    }
+    fn push_back(&mut self, value: Self::Item) {
+        let new_node = Node::new(value);
+        match self.tail.take() {
+            Some(old_tail) => {
+                new_node.borrow_mut().prev = Some(Rc::downgrade(&old_tail));
+                old_tail.borrow_mut().next = Some(Rc::clone(&new_node));
+            }
+            None => self.head = Some(Rc::clone(&new_node)),
+        }
+        self.tail = Some(new_node);
+        self.len += 1;
+    }
+    fn pop_front(&mut self) -> Option<Self::Item> {
+        let head = self.head.clone()?;
+        let node = self.unlink(head);
+        Some(Self::into_value(node))
+    }
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        let tail = self.tail.clone()?;
+        let node = self.unlink(tail);
+        Some(Self::into_value(node))
+    }
+    fn insert_at(&mut self, index: usize, value: Self::Item) {
+        assert!(index <= self.len, "index {} out of bounds", index);
+        if index == self.len {
+            return self.push_back(value);
+        }
+        let next = self.node_at(index).unwrap();
+        let prev = next.borrow().prev.as_ref().and_then(Weak::upgrade);
+        let Some(prev) = prev else {
+            return self.push_front(value);
+        };
+        let new_node = Node::new(value);
+        new_node.borrow_mut().prev = Some(Rc::downgrade(&prev));
+        next.borrow_mut().prev = Some(Rc::downgrade(&new_node));
+        new_node.borrow_mut().next = Some(next);
+        prev.borrow_mut().next = Some(new_node);
+        self.len += 1;
+    }
+    fn remove_at(&mut self, index: usize) -> Option<Self::Item> {
+        let node = self.node_at(index)?;
+        let node = self.unlink(node);
+        Some(Self::into_value(node))
+    }
+    fn remove(&mut self, value: &Self::Item) -> bool {
+        match self.find(value) {
+            Some(node) => {
+                self.unlink(node);
+                true
+            }
+            None => false,
+        }
+    }
+    fn update(&mut self, old_val: &Self::Item, new_val: Self::Item) -> Option<Self::Item> {
+        let node = self.find(old_val)?;
+        let old = std::mem::replace(&mut node.borrow_mut().value, new_val);
+        Some(old)
+    }
+    fn clear(&mut self) {
+        self.tail = None;
+        self.len = 0;
+        let mut current = self.head.take();
+        while let Some(node) = current {
+            current = node.borrow_mut().next.take();
+        }
+    }
+    /// Every node also shows its reference counts: the `Rc`s of the link
+    /// in front and of `tail`, and the `Weak` of the node after it.
+    fn layout(&self) -> Layout {
+        let location = |node: *const RefCell<Node<T>>| Location::Address(node as usize);
+        let pointer = |link: &Link<T>| {
+            link.as_ref()
+                .map(|node| Pointer::new(PointerKind::Rc, location(Rc::as_ptr(node))))
+        };
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut current = self.head.clone();
+        while let Some(node) = current {
+            let borrowed = node.borrow();
+            let mut layout = NodeLayout::new(location(Rc::as_ptr(&node)), &borrowed.value);
+            layout.next = pointer(&borrowed.next);
+            layout.prev = borrowed
+                .prev
+                .as_ref()
+                .map(|prev| Pointer::new(PointerKind::Weak, location(prev.as_ptr())));
+            layout.counts = Some(RcCounts {
+                // minus the clone held by `node` itself
+                strong: Rc::strong_count(&node) - 1,
+                weak: Rc::weak_count(&node),
+            });
+            nodes.push(layout);
+            current = borrowed.next.clone();
+        }
+        Layout {
+            roots: vec![
+                Root {
+                    name: "head",
+                    link: pointer(&self.head),
+                },
+                Root {
+                    name: "tail",
+                    link: pointer(&self.tail),
+                },
+            ],
+            nodes,
+        }
+    }
+    fn header(&self) -> &str {
+        "--- Double Linked List (Rc & Weak) CLI ---"
+    }
+}
+
+pub struct Iter<'a, T: fmt::Display + PartialEq> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    // the list can't change while the iterator holds on to its nodes
+    _list: PhantomData<&'a LinkedList<T>>,
+}
+
+impl<T: fmt::Display + PartialEq + Clone> Iterator for Iter<'_, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.front.take()?;
+        self.front = node.borrow().next.clone();
+        self.len -= 1;
+        let value = node.borrow().value.clone();
+        Some(value)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: fmt::Display + PartialEq + Clone> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.back.take()?;
+        self.back = node.borrow().prev.as_ref().and_then(Weak::upgrade);
+        self.len -= 1;
+        let value = node.borrow().value.clone();
+        Some(value)
+    }
+}
+
+impl<T: fmt::Display + PartialEq + Clone> ExactSizeIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+impl<T: fmt::Display + PartialEq + Clone> crate::tests::Snapshot for LinkedList<T> {
+    fn snapshot(&self) -> Vec<T> {
+        let values: Vec<T> = self.iter().collect();
+        // walking back along the `prev` links has to give the same list
+        let mut reversed: Vec<T> = self.iter().rev().collect();
+        reversed.reverse();
+        assert!(values == reversed, "prev links are out of sync");
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+    use crate::{LinkedListT, RcCounts};
+
+    #[test]
+    fn test_layout_counts() {
+        let list: LinkedList<i32> = {
+            let mut list = LinkedList::new();
+            for i in 1..=3 {
+                list.push_back(i);
+            }
+            list
+        };
+        let counts: Vec<_> = list.layout().nodes.iter().map(|node| node.counts).collect();
+        let rc = |strong, weak| Some(RcCounts { strong, weak });
+        // held by `head` or the node in front, the last one by `tail` too;
+        // every node but the last is pointed back at by a `Weak`
+        assert_eq!(counts, [rc(1, 1), rc(1, 1), rc(2, 0)]);
+    }
+
+    #[test]
+    fn test_pop_front_back() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+        list.push_front(5);
+        assert_eq!(list.pop_back(), Some(5));
+    }
+
+    #[test]
+    fn test_reverse_iter() {
+        let mut list = LinkedList::new();
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+        assert_eq!(list.iter().rev().collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(4));
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+}
@@ -1,8 +1,13 @@
-use std::cell::RefCell;
+mod cycle;
+
+use std::cell::{Ref, RefCell, RefMut};
 use std::fmt;
+use std::marker::PhantomData;
 use std::rc::Rc;
 
-use crate::LinkedListT;
+use crate::{DropObserver, Layout, LinkedListT, Location, NodeLayout, Pointer, PointerKind, Root};
+
+pub use cycle::{Cycle, RcCounts};
 
 type Link<T> = Option<Rc<RefCell<Node<T>>>>;
 
@@ -14,45 +19,411 @@ struct Node<T: fmt::Display + PartialEq> {
 
 pub struct LinkedList<T: fmt::Display + PartialEq> {
     head: Link<T>,
+    // kept so `push_back` doesn't have to walk the whole list
+    tail: Link<T>,
+    len: usize,
+    drop_observer: Option<DropObserver<T>>,
 }
 
-impl<T: fmt::Display + PartialEq> Drop for Node<T> {
+impl<T: fmt::Display + PartialEq> LinkedList<T> {
+    /// Iterates over handles to the nodes. A `RefCell` borrow can't outlive
+    /// the step that made it, so each handle borrows its value on demand.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.clone(),
+            len: self.len,
+            _list: PhantomData,
+        }
+    }
+    fn node_at(&self, index: usize) -> Link<T> {
+        let mut current = self.head.clone();
+        for _ in 0..index {
+            current = current?.borrow().next.clone();
+        }
+        current
+    }
+    /// Unlinks the node after `prev`, or the head if `prev` is `None`.
+    ///
+    /// A cycle that closes on the node closes on the one after it instead,
+    /// or goes away if the node was all that was left of it.
+    fn unlink_after(&mut self, prev: Link<T>) -> Rc<RefCell<Node<T>>> {
+        let node = match &prev {
+            Some(prev) => prev.borrow_mut().next.take(),
+            None => self.head.take(),
+        }
+        .expect("no node to unlink");
+        let mut next = node.borrow_mut().next.take();
+        if next.as_ref().is_some_and(|next| Rc::ptr_eq(next, &node)) {
+            next = None;
+        }
+        let tail = self.tail.clone().expect("a list with nodes has a tail");
+        if Rc::ptr_eq(&tail, &node) {
+            self.tail = prev.clone();
+        } else {
+            let mut tail = tail.borrow_mut();
+            if tail
+                .next
+                .as_ref()
+                .is_some_and(|target| Rc::ptr_eq(target, &node))
+            {
+                tail.next = next.clone();
+            }
+        }
+        match prev {
+            Some(prev) => prev.borrow_mut().next = next,
+            None => self.head = next,
+        }
+        self.len -= 1;
+        node
+    }
+    /// Takes the value out of a node [`unlink_after`](Self::unlink_after)
+    /// returned, nothing else links to it anymore.
+    fn into_value(node: Rc<RefCell<Node<T>>>) -> T {
+        match Rc::try_unwrap(node) {
+            Ok(node) => node.into_inner().value,
+            Err(_) => unreachable!("an unlinked node is still linked"),
+        }
+    }
+}
+
+// Unlinks one node at a time instead of letting each `Rc` drop the next
+// one recursively.
+impl<T: fmt::Display + PartialEq> Drop for LinkedList<T> {
     fn drop(&mut self) {
-        println!("Dropping node with value: {}", self.value);
+        self.clear();
+    }
+}
+
+impl<T: fmt::Display + PartialEq> fmt::Display for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in self {
+            write!(f, "{} -> ", *node.borrow())?;
+        }
+        write!(f, "None")
+    }
+}
+
+impl<T: fmt::Display + PartialEq + fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: fmt::Display + PartialEq + Clone> Clone for LinkedList<T> {
+    /// Copies the values into fresh nodes, the drop observer stays with the
+    /// original.
+    fn clone(&self) -> Self {
+        self.iter().map(|node| node.borrow().clone()).collect()
+    }
+}
+
+impl<T: fmt::Display + PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .zip(other)
+                .all(|(a, b)| *a.borrow() == *b.borrow())
+    }
+}
+
+impl<T: fmt::Display + PartialEq> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: fmt::Display + PartialEq> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+/// A node handed out by [`Iter`]. It keeps the list borrowed, so the node
+/// can't be unlinked while the handle exists.
+pub struct NodeRef<'a, T: fmt::Display + PartialEq> {
+    node: Rc<RefCell<Node<T>>>,
+    _list: PhantomData<&'a LinkedList<T>>,
+}
+
+impl<T: fmt::Display + PartialEq> NodeRef<'_, T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.value)
+    }
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.node.borrow_mut(), |node| &mut node.value)
+    }
+}
+
+impl<T: fmt::Display + PartialEq + fmt::Debug> fmt::Debug for NodeRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.borrow().fmt(f)
+    }
+}
+
+pub struct Iter<'a, T: fmt::Display + PartialEq> {
+    next: Link<T>,
+    len: usize,
+    _list: PhantomData<&'a LinkedList<T>>,
+}
+
+impl<'a, T: fmt::Display + PartialEq> Iterator for Iter<'a, T> {
+    type Item = NodeRef<'a, T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        // counting down instead of waiting for the `None` at the end also
+        // stops after one round of a cycle
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.next.take()?;
+        self.next = node.borrow().next.clone();
+        self.len -= 1;
+        Some(NodeRef {
+            node,
+            _list: PhantomData,
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: fmt::Display + PartialEq> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IntoIter<T: fmt::Display + PartialEq> {
+    list: LinkedList<T>,
+}
+
+impl<T: fmt::Display + PartialEq> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T: fmt::Display + PartialEq> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: fmt::Display + PartialEq> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T: fmt::Display + PartialEq> IntoIterator for &'a LinkedList<T> {
+    type Item = NodeRef<'a, T>;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
 impl<T: fmt::Display + PartialEq> LinkedListT for LinkedList<T> {
     type Item = T;
     fn new() -> Self {
-        Self { head: None }
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            drop_observer: None,
+        }
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn contains(&self, value: &Self::Item) -> bool {
+        self.iter().any(|node| *node.borrow() == *value)
+    }
+    fn get(&self, index: usize) -> Option<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.node_at(index).map(|node| node.borrow().value.clone())
+    }
+    fn to_vec(&self) -> Vec<Self::Item>
+    where
+        Self::Item: Clone,
+    {
+        self.iter().map(|node| node.borrow().clone()).collect()
     }
     fn push_front(&mut self, value: Self::Item) {
         let new_node = Rc::new(RefCell::new(Node {
             value,
             next: self.head.take(),
         }));
+        if self.tail.is_none() {
+            self.tail = Some(Rc::clone(&new_node));
+        }
         self.head = Some(new_node);
+        self.len += 1;
     }
     fn push_back(&mut self, value: Self::Item) {
-        todo!()
+        let new_node = Rc::new(RefCell::new(Node { value, next: None }));
+        match self.tail.take() {
+            Some(old_tail) => {
+                let mut old_tail = old_tail.borrow_mut();
+                // hands on the link back of a cycle, `None` otherwise
+                new_node.borrow_mut().next = old_tail.next.take();
+                old_tail.next = Some(Rc::clone(&new_node));
+            }
+            None => self.head = Some(Rc::clone(&new_node)),
+        }
+        self.tail = Some(new_node);
+        self.len += 1;
+    }
+    fn pop_front(&mut self) -> Option<Self::Item> {
+        self.remove_at(0)
     }
-    fn remove(&mut self, value: Self::Item) {
-        //let mut current = self.head.take()
-        todo!()
+    fn pop_back(&mut self) -> Option<Self::Item> {
+        self.remove_at(self.len.checked_sub(1)?)
     }
-    fn update(&mut self, old_val: Self::Item, new_val: Self::Item) {
-        todo!()
+    fn insert_at(&mut self, index: usize, value: Self::Item) {
+        assert!(index <= self.len, "index {} out of bounds", index);
+        if index == 0 {
+            return self.push_front(value);
+        }
+        if index == self.len {
+            return self.push_back(value);
+        }
+        let prev = self.node_at(index - 1).unwrap();
+        let next = prev.borrow_mut().next.take();
+        prev.borrow_mut().next = Some(Rc::new(RefCell::new(Node { value, next })));
+        self.len += 1;
     }
-    fn print(&self) {
+    fn remove_at(&mut self, index: usize) -> Option<Self::Item> {
+        if index >= self.len {
+            return None;
+        }
+        let prev = index.checked_sub(1).and_then(|i| self.node_at(i));
+        let node = self.unlink_after(prev);
+        Some(Self::into_value(node))
+    }
+    fn remove(&mut self, value: &Self::Item) -> bool {
+        let mut prev = None;
         let mut current = self.head.clone();
+        // only `len` nodes, the tail may link back into a cycle
+        for _ in 0..self.len {
+            let Some(node) = current else { break };
+            if node.borrow().value == *value {
+                let removed = self.unlink_after(prev);
+                if let Some(observer) = &mut self.drop_observer {
+                    observer(&removed.borrow().value);
+                }
+                return true;
+            }
+            current = node.borrow().next.clone();
+            prev = Some(node);
+        }
+        false
+    }
+    fn update(&mut self, old_val: &Self::Item, new_val: Self::Item) -> Option<Self::Item> {
+        let node = self.iter().find(|node| *node.borrow() == *old_val)?;
+        let old = std::mem::replace(&mut *node.borrow_mut(), new_val);
+        Some(old)
+    }
+    fn clear(&mut self) {
+        self.tail = None;
+        self.len = 0;
+        let mut current = self.head.take();
         while let Some(node) = current {
-            print!("{} -> ", node.borrow().value);
+            // a node someone else still holds is left to them, together
+            // with the rest of the list behind it
+            let Ok(node) = Rc::try_unwrap(node) else {
+                break;
+            };
+            let mut node = node.into_inner();
+            current = node.next.take();
+            if let Some(observer) = &mut self.drop_observer {
+                observer(&node.value);
+            }
+        }
+    }
+    /// Every node also shows its reference counts. Walks only `len` nodes,
+    /// so a cycle shows up as a `next` pointing back.
+    fn layout(&self) -> Layout {
+        let location = |node: &Rc<RefCell<Node<T>>>| Location::Address(Rc::as_ptr(node) as usize);
+        let pointer = |link: &Link<T>| {
+            link.as_ref()
+                .map(|node| Pointer::new(PointerKind::Rc, location(node)))
+        };
+        // counted before `current` adds a reference of its own
+        let counts = self.ref_counts();
+        let mut nodes = Vec::with_capacity(self.len);
+        let mut current = self.head.clone();
+        for counts in counts {
+            let node = current.expect("ref_counts stops at the last node");
+            let mut layout = NodeLayout::new(location(&node), &node.borrow().value);
+            layout.next = pointer(&node.borrow().next);
+            layout.counts = Some(counts);
+            nodes.push(layout);
             current = node.borrow().next.clone();
         }
-        println!("None");
+        Layout {
+            roots: vec![
+                Root {
+                    name: "head",
+                    link: pointer(&self.head),
+                },
+                Root {
+                    name: "tail",
+                    link: pointer(&self.tail),
+                },
+            ],
+            nodes,
+        }
     }
     fn header(&self) -> &str {
         "--- Single Linked List (Rc & RefCell) CLI ---"
     }
+    fn set_drop_observer(&mut self, observer: DropObserver<Self::Item>) {
+        self.drop_observer = Some(observer);
+    }
+}
+
+#[cfg(test)]
+impl<T: fmt::Display + PartialEq + Clone> crate::tests::Snapshot for LinkedList<T> {
+    fn snapshot(&self) -> Vec<T> {
+        self.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+    use crate::LinkedListT;
+
+    #[test]
+    fn test_iter() {
+        let list: LinkedList<i32> = (1..=4).collect();
+        for node in &list {
+            *node.borrow_mut() *= 10;
+        }
+        let values: Vec<i32> = list.iter().map(|node| *node.borrow()).collect();
+        assert_eq!(values, vec![10, 20, 30, 40]);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn test_extend_clone_eq() {
+        let mut list: LinkedList<i32> = LinkedList::new();
+        list.extend([1, 2]);
+        list.push_front(0);
+        assert_eq!(format!("{:?}", list), "[0, 1, 2]");
+        let mut copy = list.clone();
+        assert_eq!(copy, list);
+        // the clone has nodes of its own
+        copy.update(&1, 10);
+        assert_eq!(list.get(1), Some(1));
+        assert_ne!(copy, list);
+        copy.push_back(3);
+        assert_eq!(copy.len(), 4);
+    }
 }
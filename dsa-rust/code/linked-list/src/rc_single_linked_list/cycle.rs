@@ -0,0 +1,319 @@
+//! Reference cycles: making one on purpose, finding it and taking it apart.
+//!
+//! `Rc` frees a node once nothing points at it anymore. When the tail
+//! links back into the list every node in the loop is pointed at by its
+//! predecessor, so none of them is ever freed, even after the list itself
+//! is gone.
+
+use std::cell::RefCell;
+use std::fmt::{self, Write};
+use std::rc::Rc;
+
+use super::{LinkedList, Node};
+
+type NodeRc<T> = Rc<RefCell<Node<T>>>;
+
+/// Where a cycle starts and how many nodes go around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Index of the first node that is part of the cycle.
+    pub start: usize,
+    pub len: usize,
+}
+
+/// The reference counts of one node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RcCounts {
+    pub strong: usize,
+    pub weak: usize,
+}
+
+fn next<T: fmt::Display + PartialEq>(node: &NodeRc<T>) -> Option<NodeRc<T>> {
+    node.borrow().next.clone()
+}
+
+impl<T: fmt::Display + PartialEq> LinkedList<T> {
+    /// Points the tail back at the node at `index`, closing a cycle.
+    ///
+    /// Removing the node the tail points at moves the cycle on to the
+    /// node after it, and a node appended at the back links back where the
+    /// old tail did. `sort` takes every node out and puts the values back,
+    /// which leaves the list without a cycle. Dropping the list while the cycle is in place leaks
+    /// every node from `index` on, [`break_cycle`](Self::break_cycle)
+    /// first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    pub fn link_tail_to(&mut self, index: usize) {
+        assert!(index < self.len, "index {} out of bounds", index);
+        let target = self.node_at(index);
+        self.tail.as_ref().unwrap().borrow_mut().next = target;
+    }
+
+    /// Floyd's tortoise and hare. The hare moves two nodes per step and the
+    /// tortoise one, inside a cycle the hare catches up with it.
+    ///
+    /// From the meeting point the start of the cycle is as far away as it
+    /// is from the head, so one pointer from each, walking in step, meet
+    /// there. Going around once more gives the length.
+    /// O(n) time, O(1) space.
+    pub fn find_cycle_floyd(&self) -> Option<Cycle> {
+        let head = self.head.clone()?;
+        let mut tortoise = Rc::clone(&head);
+        let mut hare = Rc::clone(&head);
+        loop {
+            tortoise = next(&tortoise)?;
+            hare = next(&next(&hare)?)?;
+            if Rc::ptr_eq(&tortoise, &hare) {
+                break;
+            }
+        }
+        let mut start = 0;
+        tortoise = head;
+        while !Rc::ptr_eq(&tortoise, &hare) {
+            tortoise = next(&tortoise).unwrap();
+            hare = next(&hare).unwrap();
+            start += 1;
+        }
+        let mut len = 1;
+        hare = next(&tortoise).unwrap();
+        while !Rc::ptr_eq(&tortoise, &hare) {
+            hare = next(&hare).unwrap();
+            len += 1;
+        }
+        Some(Cycle { start, len })
+    }
+
+    /// Brent's algorithm. The tortoise waits while the hare runs ahead
+    /// 1, 2, 4, ... nodes and jumps to the hare whenever the power of two
+    /// runs out. When they meet, the hare's distance is the cycle length,
+    /// no extra lap needed.
+    ///
+    /// A pointer `len` nodes ahead of another then meets it at the start of
+    /// the cycle. Still O(n) time and O(1) space, but fewer steps than
+    /// Floyd's.
+    pub fn find_cycle_brent(&self) -> Option<Cycle> {
+        let head = self.head.clone()?;
+        let mut power = 1;
+        let mut len = 1;
+        let mut tortoise = Rc::clone(&head);
+        let mut hare = next(&head)?;
+        while !Rc::ptr_eq(&tortoise, &hare) {
+            if power == len {
+                tortoise = Rc::clone(&hare);
+                power *= 2;
+                len = 0;
+            }
+            hare = next(&hare)?;
+            len += 1;
+        }
+        tortoise = Rc::clone(&head);
+        hare = head;
+        for _ in 0..len {
+            hare = next(&hare).unwrap();
+        }
+        let mut start = 0;
+        while !Rc::ptr_eq(&tortoise, &hare) {
+            tortoise = next(&tortoise).unwrap();
+            hare = next(&hare).unwrap();
+            start += 1;
+        }
+        Some(Cycle { start, len })
+    }
+
+    /// Cuts the link that closes the cycle, if there is one, and returns
+    /// where the cycle was.
+    pub fn break_cycle(&mut self) -> Option<Cycle> {
+        let cycle = self.find_cycle_floyd()?;
+        // the last node of the loop is the one linking back to its start
+        let last = self.node_at(cycle.start + cycle.len - 1).unwrap();
+        last.borrow_mut().next = None;
+        self.tail = Some(last);
+        Some(cycle)
+    }
+
+    /// The reference counts of every node, from the head on.
+    ///
+    /// A node is held by the link in front of it, the tail field adds one
+    /// for the last node and a cycle adds one for the node it closes on.
+    pub fn ref_counts(&self) -> Vec<RcCounts> {
+        let mut counts = Vec::with_capacity(self.len);
+        let mut current = self.head.clone();
+        for _ in 0..self.len {
+            let Some(node) = current else { break };
+            counts.push(RcCounts {
+                // minus the clone held by `node` itself
+                strong: Rc::strong_count(&node) - 1,
+                weak: Rc::weak_count(&node),
+            });
+            current = next(&node);
+        }
+        counts
+    }
+
+    /// [`ref_counts`](Self::ref_counts) as a table, one node per line.
+    pub fn ref_count_report(&self) -> String {
+        let mut report = String::from("index  value  strong  weak\n");
+        for (index, (node, counts)) in self.iter().zip(self.ref_counts()).enumerate() {
+            writeln!(
+                report,
+                "{:>5}  {:>5}  {:>6}  {:>4}",
+                index,
+                node.borrow().to_string(),
+                counts.strong,
+                counts.weak
+            )
+            .unwrap();
+        }
+        if let Some(cycle) = self.find_cycle_floyd() {
+            writeln!(
+                report,
+                "cycle of {} nodes from index {}, they leak when the list is dropped",
+                cycle.len, cycle.start
+            )
+            .unwrap();
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::{Cycle, LinkedList, RcCounts};
+    use crate::LinkedListT;
+
+    fn list(len: usize) -> LinkedList<usize> {
+        (0..len).collect()
+    }
+
+    #[test]
+    fn test_no_cycle() {
+        for len in 0..4 {
+            let l = list(len);
+            assert_eq!(l.find_cycle_floyd(), None);
+            assert_eq!(l.find_cycle_brent(), None);
+        }
+        assert_eq!(list(3).break_cycle(), None);
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        for len in 1..12 {
+            for start in 0..len {
+                let mut l = list(len);
+                l.link_tail_to(start);
+                let expected = Cycle {
+                    start,
+                    len: len - start,
+                };
+                assert_eq!(l.find_cycle_floyd(), Some(expected));
+                assert_eq!(l.find_cycle_brent(), Some(expected));
+                // iterating stops after one round
+                assert_eq!(l.iter().count(), len);
+                assert_eq!(l.break_cycle(), Some(expected));
+                assert_eq!(l.find_cycle_floyd(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_break_cycle_restores_tail() {
+        let mut l = list(4);
+        l.link_tail_to(1);
+        l.break_cycle();
+        l.push_back(4);
+        assert_eq!(l.to_string(), "0 -> 1 -> 2 -> 3 -> 4 -> None");
+        assert_eq!(l.pop_back(), Some(4));
+        assert_eq!(l.pop_front(), Some(0));
+    }
+
+    #[test]
+    fn test_ref_counts() {
+        let mut l = list(3);
+        let counts = |strong| RcCounts { strong, weak: 0 };
+        assert_eq!(l.ref_counts(), vec![counts(1), counts(1), counts(2)]);
+        l.link_tail_to(1);
+        assert_eq!(l.ref_counts(), vec![counts(1), counts(2), counts(2)]);
+        assert!(l
+            .ref_count_report()
+            .contains("cycle of 2 nodes from index 1"));
+        // the layout shows the cycle as the tail pointing back
+        let layout = l.layout();
+        assert_eq!(
+            layout.nodes[2].next.unwrap().target,
+            layout.nodes[1].location
+        );
+        let layout_counts: Vec<_> = layout.nodes.iter().map(|node| node.counts).collect();
+        assert_eq!(layout_counts, [counts(1), counts(2), counts(2)].map(Some));
+        l.break_cycle();
+    }
+
+    #[test]
+    fn test_remove_from_cycle() {
+        let mut l = list(5);
+        l.link_tail_to(1);
+        assert_eq!(l.pop_front(), Some(0));
+        // the node the cycle closes on, it moves on to the next one
+        assert_eq!(l.remove_at(0), Some(1));
+        assert_eq!(l.find_cycle_floyd(), Some(Cycle { start: 0, len: 3 }));
+        assert!(!l.remove(&9));
+        // the tail, the one before it takes over the link back
+        assert_eq!(l.pop_back(), Some(4));
+        assert_eq!(l.find_cycle_floyd(), Some(Cycle { start: 0, len: 2 }));
+        assert!(l.remove(&2));
+        assert_eq!(l.find_cycle_floyd(), Some(Cycle { start: 0, len: 1 }));
+        // the last node linked to itself
+        assert_eq!(l.pop_front(), Some(3));
+        assert!(l.is_empty());
+        assert_eq!(l.pop_back(), None);
+    }
+
+    #[test]
+    fn test_append_to_cycle() {
+        let mut l = list(3);
+        l.link_tail_to(1);
+        l.push_back(9);
+        assert_eq!(l.find_cycle_floyd(), Some(Cycle { start: 1, len: 3 }));
+        l.insert_at(4, 5);
+        assert_eq!(l.find_cycle_floyd(), Some(Cycle { start: 1, len: 4 }));
+        assert_eq!(l.to_vec(), vec![0, 1, 2, 9, 5]);
+        l.sort();
+        assert_eq!(l.find_cycle_floyd(), None);
+        assert_eq!(l.to_vec(), vec![0, 1, 2, 5, 9]);
+
+        // a tail that links to itself
+        let mut l = list(2);
+        l.link_tail_to(1);
+        l.push_back(2);
+        assert_eq!(l.find_cycle_floyd(), Some(Cycle { start: 1, len: 2 }));
+        assert_eq!(l.break_cycle(), Some(Cycle { start: 1, len: 2 }));
+        assert_eq!(l.to_string(), "0 -> 1 -> 2 -> None");
+    }
+
+    // leaks on purpose, which Miri would report
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_cycle_leaks() {
+        let dropped = Arc::new(AtomicUsize::new(0));
+        for (break_it, expected) in [(false, 1), (true, 4)] {
+            dropped.store(0, Ordering::Relaxed);
+            let mut l = list(4);
+            let counter = Arc::clone(&dropped);
+            l.set_drop_observer(Box::new(move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }));
+            l.link_tail_to(1);
+            if break_it {
+                l.break_cycle();
+            }
+            // with the cycle in place only the head is freed, the node the
+            // tail points back at is still held
+            drop(l);
+            assert_eq!(dropped.load(Ordering::Relaxed), expected);
+        }
+    }
+}
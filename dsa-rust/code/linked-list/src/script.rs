@@ -0,0 +1,447 @@
+//! The commands behind the interactive menu, also readable from a script.
+//!
+//! A script has one command per line, blank lines and lines starting with
+//! `#` are skipped:
+//!
+//! ```text
+//! # build 1 -> 2 -> 3
+//! push_back 2
+//! push_back 3
+//! push_front 1
+//! remove_at 1
+//! print
+//! ```
+//!
+//! | command                 | does                                       |
+//! |-------------------------|--------------------------------------------|
+//! | `push_front VALUE`      | adds `VALUE` in front                      |
+//! | `push_back VALUE`       | adds `VALUE` at the end                    |
+//! | `remove VALUE`          | removes the first element equal to `VALUE` |
+//! | `update OLD NEW`        | replaces the first `OLD` with `NEW`        |
+//! | `print`                 | prints the list                            |
+//! | `insert_at INDEX VALUE` | inserts `VALUE` so it ends up at `INDEX`   |
+//! | `remove_at INDEX`       | removes and prints the element at `INDEX`  |
+//! | `get INDEX`             | prints the element at `INDEX`              |
+//! | `pop_front`             | removes and prints the first element       |
+//! | `pop_back`              | removes and prints the last element        |
+//! | `len`                   | prints the length                          |
+//! | `clear`                 | removes every element                      |
+//! | `sort`                  | sorts the list                             |
+//! | `layout`                | draws the nodes as they are in memory      |
+//! | `dot`                   | prints the same as a Graphviz graph        |
+//! | `save FILE [FORMAT]`    | writes the list to `FILE`                  |
+//! | `load FILE [FORMAT]`    | replaces the list with the one in `FILE`   |
+//! | `exit`                  | stops, the rest of the script is ignored   |
+//!
+//! `FORMAT` is text, json or csv, by default it goes by the extension of
+//! `FILE`, see [`Format::from_path`].
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+};
+
+use crate::{read_values, save_file, Element, Format, LinkedListT, Options, StorageError};
+
+/// One operation on a list, read from the menu or a script line.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Command<U> {
+    PushFront(U),
+    PushBack(U),
+    Remove(U),
+    Update(U, U),
+    Print,
+    InsertAt(usize, U),
+    RemoveAt(usize),
+    Get(usize),
+    PopFront,
+    PopBack,
+    Len,
+    Clear,
+    Sort,
+    Layout,
+    Dot,
+    /// A file and its format, `None` goes by the extension.
+    Save(String, Option<Format>),
+    Load(String, Option<Format>),
+    Exit,
+}
+
+fn value<U: Element>(word: &str) -> Result<U, String> {
+    word.parse()
+        .map_err(|_| format!("invalid value '{}', expected {}", word, U::NAME))
+}
+
+fn index(word: &str) -> Result<usize, String> {
+    word.parse()
+        .map_err(|_| format!("invalid index '{}'", word))
+}
+
+impl<U: Element> Command<U> {
+    /// Parses one script line, `None` for a blank line or a comment.
+    /// Values are single words, a `String` can't hold a space.
+    pub(crate) fn parse(line: &str) -> Result<Option<Self>, String> {
+        let line = line.trim();
+        if line.starts_with('#') {
+            return Ok(None);
+        }
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Ok(None);
+        };
+        let args: Vec<&str> = words.collect();
+        let (min, max) = match name {
+            "push_front" | "push_back" | "remove" | "remove_at" | "get" => (1, 1),
+            "update" | "insert_at" => (2, 2),
+            "save" | "load" => (1, 2),
+            "print" | "pop_front" | "pop_back" | "len" | "clear" | "sort" | "layout" | "dot"
+            | "exit" => (0, 0),
+            _ => return Err(format!("unknown command '{}'", name)),
+        };
+        if args.len() < min || args.len() > max {
+            let expected = match (min, max) {
+                (1, 1) => "1 argument".to_owned(),
+                _ if min == max => format!("{} arguments", min),
+                _ => format!("{} or {} arguments", min, max),
+            };
+            return Err(format!("{} expects {}, got {}", name, expected, args.len()));
+        }
+        let format = || args.get(1).map(|format| format.parse()).transpose();
+        let command = match name {
+            "push_front" => Self::PushFront(value(args[0])?),
+            "push_back" => Self::PushBack(value(args[0])?),
+            "remove" => Self::Remove(value(args[0])?),
+            "update" => Self::Update(value(args[0])?, value(args[1])?),
+            "print" => Self::Print,
+            "insert_at" => Self::InsertAt(index(args[0])?, value(args[1])?),
+            "remove_at" => Self::RemoveAt(index(args[0])?),
+            "get" => Self::Get(index(args[0])?),
+            "pop_front" => Self::PopFront,
+            "pop_back" => Self::PopBack,
+            "len" => Self::Len,
+            "clear" => Self::Clear,
+            "sort" => Self::Sort,
+            "layout" => Self::Layout,
+            "dot" => Self::Dot,
+            "save" => Self::Save(args[0].to_owned(), format()?),
+            "load" => Self::Load(args[0].to_owned(), format()?),
+            _ => Self::Exit,
+        };
+        Ok(Some(command))
+    }
+
+    /// Runs the command on `list`. `Ok` holds the line to print, if the
+    /// command prints one, `Err` says why the command failed.
+    ///
+    /// `Exit` does nothing here, stopping is up to the caller.
+    pub(crate) fn execute<T>(
+        self,
+        list: &mut T,
+        options: &Options,
+    ) -> Result<Option<String>, String>
+    where
+        T: LinkedListT<Item = U>,
+    {
+        match self {
+            Self::PushFront(value) => list.push_front(value),
+            Self::PushBack(value) => list.push_back(value),
+            Self::Remove(value) => match find(list, &value, options.tolerance) {
+                Some(found) => {
+                    list.remove(&found);
+                }
+                None => return Err(format!("Value {} not found.", value)),
+            },
+            Self::Update(old_value, new_value) => match find(list, &old_value, options.tolerance) {
+                Some(found) => {
+                    list.update(&found, new_value);
+                }
+                None => return Err(format!("Value {} not found.", old_value)),
+            },
+            Self::Print => return Ok(Some(list.to_string())),
+            Self::InsertAt(index, value) => {
+                if index > list.len() {
+                    return Err(format!("Index {} out of range 0 to {}.", index, list.len()));
+                }
+                list.insert_at(index, value);
+            }
+            Self::RemoveAt(index) => {
+                return match list.remove_at(index) {
+                    Some(value) => Ok(Some(format!("Removed {}.", value))),
+                    None => Err(out_of_range(index, list.len())),
+                }
+            }
+            Self::Get(index) => {
+                return match list.get(index) {
+                    Some(value) => Ok(Some(format!("Value at {}: {}", index, value))),
+                    None => Err(out_of_range(index, list.len())),
+                }
+            }
+            Self::PopFront | Self::PopBack => {
+                let popped = match self {
+                    Self::PopFront => list.pop_front(),
+                    _ => list.pop_back(),
+                };
+                return match popped {
+                    Some(value) => Ok(Some(format!("Popped {}.", value))),
+                    None => Err("List is empty.".to_owned()),
+                };
+            }
+            Self::Len => return Ok(Some(format!("Length: {}", list.len()))),
+            Self::Clear => list.clear(),
+            Self::Sort => list.sort(),
+            Self::Layout => return Ok(Some(list.layout().to_string())),
+            Self::Dot => return Ok(Some(list.layout().to_dot().trim_end().to_owned())),
+            Self::Save(path, format) => {
+                let format = format.unwrap_or_else(|| Format::from_path(&path));
+                return match save_file(list, format, &path) {
+                    Ok(()) => Ok(Some(format!(
+                        "Saved {} to {}.",
+                        value_count(list.len()),
+                        path
+                    ))),
+                    Err(error) => Err(format!("Cannot save to {}: {}", path, error)),
+                };
+            }
+            Self::Load(path, format) => {
+                let format = format.unwrap_or_else(|| Format::from_path(&path));
+                let values = File::open(&path)
+                    .map_err(StorageError::from)
+                    .and_then(|file| read_values::<U>(format, BufReader::new(file)));
+                return match values {
+                    Ok(loaded) => {
+                        let count = loaded.len();
+                        list.clear();
+                        for value in loaded {
+                            list.push_back(value);
+                        }
+                        Ok(Some(format!(
+                            "Loaded {} from {}.",
+                            value_count(count),
+                            path
+                        )))
+                    }
+                    Err(error) => Err(format!("Cannot load {}: {}", path, error)),
+                };
+            }
+            Self::Exit => {}
+        }
+        Ok(None)
+    }
+}
+
+/// The first element of `list` that [matches](Element::matches) `value`.
+///
+/// Without a tolerance that is `value` itself, if the list contains it.
+/// With one every element has to be looked at, in a single pass over a
+/// copy of the list.
+fn find<U, T>(list: &T, value: &U, tolerance: f64) -> Option<U>
+where
+    U: Element,
+    T: LinkedListT<Item = U>,
+{
+    if tolerance == 0.0 {
+        return list.contains(value).then(|| value.clone());
+    }
+    list.to_vec()
+        .into_iter()
+        .find(|element| element.matches(value, tolerance))
+}
+
+/// `1 value`, `2 values`.
+fn value_count(count: usize) -> String {
+    match count {
+        1 => "1 value".to_owned(),
+        _ => format!("{} values", count),
+    }
+}
+
+fn out_of_range(index: usize, len: usize) -> String {
+    match len {
+        0 => "List is empty.".to_owned(),
+        _ => format!("Index {} out of range 0 to {}.", index, len - 1),
+    }
+}
+
+/// How a script ended, see [`ScriptStatus::code`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptStatus {
+    /// Every command ran.
+    Ok,
+    /// Every command ran, but at least one of them failed, like removing
+    /// a value that isn't there.
+    Failed,
+    /// A line isn't a valid command. Nothing after it has run.
+    Invalid,
+}
+
+impl ScriptStatus {
+    /// The exit code of the binary: 0, 1 and 2 in the order of the variants.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Runs the script read from `input` on a new, empty `T`.
+///
+/// What the commands print goes to `out`. Failed commands and invalid lines
+/// are reported on `err` with their line number. A failed command doesn't
+/// stop the script, an invalid line does. The list doesn't report its
+/// drops, so every kind of list prints the same for the same script.
+pub fn run_script<U, T>(
+    input: impl BufRead,
+    out: &mut impl Write,
+    err: &mut impl Write,
+    options: &Options,
+) -> io::Result<ScriptStatus>
+where
+    T: LinkedListT<Item = U>,
+    U: Element,
+{
+    let mut list = T::new();
+    let mut status = ScriptStatus::Ok;
+    for (number, line) in input.lines().enumerate() {
+        let command = match Command::parse(&line?) {
+            Ok(Some(Command::Exit)) => break,
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(message) => {
+                writeln!(err, "line {}: {}", number + 1, message)?;
+                return Ok(ScriptStatus::Invalid);
+            }
+        };
+        match command.execute(&mut list, options) {
+            Ok(Some(output)) => writeln!(out, "{}", output)?,
+            Ok(None) => {}
+            Err(message) => {
+                writeln!(err, "line {}: {}", number + 1, message)?;
+                status = ScriptStatus::Failed;
+            }
+        }
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_script, Command, ScriptStatus};
+    use crate::{BoxSingleLinkedList, Element, LinkedListT, Options, RcDoubleLinkedList};
+
+    fn run_with<U: Element, T: LinkedListT<Item = U>>(
+        script: &str,
+        options: &Options,
+    ) -> (String, String, ScriptStatus) {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let status = run_script::<U, T>(script.as_bytes(), &mut out, &mut err, options).unwrap();
+        (
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+            status,
+        )
+    }
+
+    fn run<T: LinkedListT<Item = i32>>(script: &str) -> (String, String, ScriptStatus) {
+        run_with::<i32, T>(script, &Options::default())
+    }
+
+    #[test]
+    fn test_parse() {
+        let parse = Command::<i32>::parse;
+        assert_eq!(parse("  # comment"), Ok(None));
+        assert_eq!(parse(""), Ok(None));
+        assert_eq!(parse("insert_at 2 -5"), Ok(Some(Command::InsertAt(2, -5))));
+        assert_eq!(parse("update 1 2"), Ok(Some(Command::Update(1, 2))));
+        assert_eq!(parse(" pop_back "), Ok(Some(Command::PopBack)));
+        assert_eq!(parse("shuffle"), Err("unknown command 'shuffle'".into()));
+        assert_eq!(
+            parse("push_front"),
+            Err("push_front expects 1 argument, got 0".into())
+        );
+        assert_eq!(parse("len 1"), Err("len expects 0 arguments, got 1".into()));
+        assert_eq!(
+            parse("push_back x"),
+            Err("invalid value 'x', expected i32".into())
+        );
+        assert_eq!(parse("get -1"), Err("invalid index '-1'".into()));
+    }
+
+    #[test]
+    fn test_run_script() {
+        let (out, err, status) = run::<BoxSingleLinkedList<i32>>(
+            "push_back 2\npush_back 3\npush_front 1\nget 2\nremove_at 1\nprint\nlen\n",
+        );
+        assert_eq!(
+            out,
+            "Value at 2: 3\nRemoved 2.\n1 -> 3 -> None\nLength: 2\n"
+        );
+        assert_eq!(err, "");
+        assert_eq!(status, ScriptStatus::Ok);
+    }
+
+    #[test]
+    fn test_failures_continue() {
+        let (out, err, status) =
+            run::<BoxSingleLinkedList<i32>>("pop_front\nremove 4\npush_front 4\nget 1\nprint\n");
+        assert_eq!(out, "4 -> None\n");
+        assert_eq!(
+            err,
+            "line 1: List is empty.\nline 2: Value 4 not found.\nline 4: Index 1 out of range 0 to 0.\n"
+        );
+        assert_eq!(status, ScriptStatus::Failed);
+    }
+
+    #[test]
+    fn test_invalid_line_stops() {
+        let (out, err, status) =
+            run::<BoxSingleLinkedList<i32>>("push_front 1\nprint\npush_front one\nprint\n");
+        assert_eq!(out, "1 -> None\n");
+        assert_eq!(err, "line 3: invalid value 'one', expected i32\n");
+        assert_eq!(status, ScriptStatus::Invalid);
+        assert_eq!(status.code(), 2);
+    }
+
+    #[test]
+    fn test_exit() {
+        let (out, _, status) = run::<BoxSingleLinkedList<i32>>("len\nexit\nlen\n");
+        assert_eq!(out, "Length: 0\n");
+        assert_eq!(status, ScriptStatus::Ok);
+    }
+
+    #[test]
+    fn test_element_types() {
+        let (out, err, _) = run_with::<String, BoxSingleLinkedList<String>>(
+            "push_back b\npush_front a\nupdate b c\nprint\n",
+            &Options::default(),
+        );
+        assert_eq!((out.as_str(), err.as_str()), ("a -> c -> None\n", ""));
+        let (_, err, status) =
+            run_with::<char, BoxSingleLinkedList<char>>("push_back ab\n", &Options::default());
+        assert_eq!(err, "line 1: invalid value 'ab', expected char\n");
+        assert_eq!(status, ScriptStatus::Invalid);
+        let (_, err, _) =
+            run_with::<i64, BoxSingleLinkedList<i64>>("push_back 1.5\n", &Options::default());
+        assert_eq!(err, "line 1: invalid value '1.5', expected i64\n");
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let script = "push_back 0.30000000000000004\npush_back 2.5\n\
+                      remove 0.3\nupdate 2.49 7\nprint\n";
+        let (out, err, status) =
+            run_with::<f64, RcDoubleLinkedList<f64>>(script, &Options::default());
+        assert_eq!(out, "None <-> 0.30000000000000004 <-> 2.5 <-> None\n");
+        assert_eq!(
+            err,
+            "line 3: Value 0.3 not found.\nline 4: Value 2.49 not found.\n"
+        );
+        assert_eq!(status, ScriptStatus::Failed);
+
+        let options = Options {
+            tolerance: 0.02,
+            ..Options::default()
+        };
+        let (out, err, status) = run_with::<f64, RcDoubleLinkedList<f64>>(script, &options);
+        assert_eq!(out, "None <-> 7 <-> None\n");
+        assert_eq!(err, "");
+        assert_eq!(status, ScriptStatus::Ok);
+    }
+}
@@ -0,0 +1,512 @@
+//! A skip list: a sorted linked list with express lanes, used as an
+//! ordered map.
+//!
+//! Every node is on level 1, the plain sorted list. A node also reaches
+//! each next level with a chance of one half, so level 2 skips about every
+//! other node, level 3 three out of four, and so on. A search starts on
+//! the highest level and drops down a level whenever the next node would
+//! overshoot, which takes O(log n) steps on average.
+//!
+//! ```text
+//! level 3: head ------------------------> 30 -----------------> None
+//! level 2: head --------> 10 -----------> 30 --------> 50 ----> None
+//! level 1: head --> 5 --> 10 --> 20 ----> 30 --> 40 --> 50 ----> None
+//! ```
+//!
+//! The levels come from a seeded random number generator, so the same
+//! seed and the same operations always build the same list. Like the
+//! arena list, the nodes live in a `Vec` and link to each other by index.
+
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    ops::{Bound, RangeBounds},
+};
+
+/// The most levels a node can have, enough for 2³² nodes.
+const MAX_LEVEL: usize = 32;
+const DEFAULT_SEED: u64 = 0x5EED;
+
+/// SplitMix64, small and fast, and every seed gives a good sequence.
+#[derive(Debug, Clone)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    /// The next node on each level the node reaches, level 1 first.
+    next: Vec<Option<usize>>,
+}
+
+pub struct SkipList<K, V> {
+    /// `None` for a slot a removed node left, reused by the next insert.
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    /// The first node on each level, as many as the highest node has.
+    head: Vec<Option<usize>>,
+    len: usize,
+    rng: SplitMix64,
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    /// An empty list with a fixed seed.
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+    /// An empty list whose levels are drawn from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: Vec::new(),
+            len: 0,
+            rng: SplitMix64 { state: seed },
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn node(&self, index: usize) -> &Node<K, V> {
+        self.nodes[index].as_ref().expect("link to a free slot")
+    }
+    fn node_mut(&mut self, index: usize) -> &mut Node<K, V> {
+        self.nodes[index].as_mut().expect("link to a free slot")
+    }
+    /// The node after `at` on `level`, `at == None` being the head.
+    fn next(&self, at: Option<usize>, level: usize) -> Option<usize> {
+        match at {
+            Some(index) => self.node(index).next[level],
+            None => self.head.get(level).copied().flatten(),
+        }
+    }
+    fn set_next(&mut self, at: Option<usize>, level: usize, next: Option<usize>) {
+        match at {
+            Some(index) => self.node_mut(index).next[level] = next,
+            None => self.head[level] = next,
+        }
+    }
+    /// How many levels a new node reaches: one more for every coin flip
+    /// that comes up heads.
+    fn random_level(&mut self) -> usize {
+        (self.rng.next().trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+    /// On every level, the last node that `goes_past` says to step over,
+    /// `None` for the head.
+    fn predecessors(&self, goes_past: impl Fn(&K) -> bool) -> [Option<usize>; MAX_LEVEL] {
+        let mut predecessors = [None; MAX_LEVEL];
+        let mut current = None;
+        for level in (0..self.head.len()).rev() {
+            while let Some(next) = self.next(current, level) {
+                if !goes_past(&self.node(next).key) {
+                    break;
+                }
+                current = Some(next);
+            }
+            predecessors[level] = current;
+        }
+        predecessors
+    }
+    /// The first node that `goes_past` doesn't say to step over.
+    fn first_not_past(&self, goes_past: impl Fn(&K) -> bool) -> Option<usize> {
+        self.next(self.predecessors(goes_past)[0], 0)
+    }
+    /// The node with `key`.
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.first_not_past(|k| k.borrow() < key)?;
+        (self.node(index).key.borrow() == key).then_some(index)
+    }
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).map(|index| &self.node(index).value)
+    }
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let index = self.find(key)?;
+        Some(&mut self.node_mut(index).value)
+    }
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+    /// Stores `value` for `key`, returning the value it replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let predecessors = self.predecessors(|k| *k < key);
+        if let Some(index) = self.next(predecessors[0], 0) {
+            let node = self.node_mut(index);
+            if node.key == key {
+                return Some(std::mem::replace(&mut node.value, value));
+            }
+        }
+        let level = self.random_level();
+        // a node taller than all others starts new levels at the head,
+        // `predecessors` is already `None` there
+        if self.head.len() < level {
+            self.head.resize(level, None);
+        }
+        let next = (0..level).map(|l| self.next(predecessors[l], l)).collect();
+        let node = Some(Node { key, value, next });
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        for (l, &predecessor) in predecessors.iter().enumerate().take(level) {
+            self.set_next(predecessor, l, Some(index));
+        }
+        self.len += 1;
+        None
+    }
+    /// Removes the entry for `key` and returns its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let predecessors = self.predecessors(|k| k.borrow() < key);
+        let index = self.next(predecessors[0], 0)?;
+        if self.node(index).key.borrow() != key {
+            return None;
+        }
+        let node = self.nodes[index].take().expect("link to a free slot");
+        for (l, &next) in node.next.iter().enumerate() {
+            self.set_next(predecessors[l], l, next);
+        }
+        self.free.push(index);
+        // levels only the removed node reached are gone
+        while self.head.last() == Some(&None) {
+            self.head.pop();
+        }
+        self.len -= 1;
+        Some(node.value)
+    }
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let node = self.node(self.next(None, 0)?);
+        Some((&node.key, &node.value))
+    }
+    /// The entry with the largest key, going as far right as possible on
+    /// every level. O(log n).
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let index = self.predecessors(|_| true)[0]?;
+        let node = self.node(index);
+        Some((&node.key, &node.value))
+    }
+    /// The entries with a key in `range`, in ascending order.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.first_not_past(|k| k.borrow() < start),
+            Bound::Excluded(start) => self.first_not_past(|k| k.borrow() <= start),
+            Bound::Unbounded => self.next(None, 0),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.first_not_past(|k| k.borrow() <= end),
+            Bound::Excluded(end) => self.first_not_past(|k| k.borrow() < end),
+            Bound::Unbounded => None,
+        };
+        // a range that ends before it starts is empty
+        let empty = match (start, end) {
+            (Some(start), Some(end)) => {
+                self.node(start).key.cmp(&self.node(end).key) == Ordering::Greater
+            }
+            _ => false,
+        };
+        Iter {
+            list: self,
+            next: if empty { end } else { start },
+            end,
+        }
+    }
+    /// All entries in ascending order of their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            list: self,
+            next: self.next(None, 0),
+            end: None,
+        }
+    }
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.head.clear();
+        self.len = 0;
+    }
+    /// How many nodes reach each level, for a look at how the coin flips
+    /// came out.
+    pub fn level_histogram(&self) -> LevelHistogram {
+        let mut counts = vec![0; self.head.len()];
+        for node in self.nodes.iter().flatten() {
+            for count in &mut counts[..node.next.len()] {
+                *count += 1;
+            }
+        }
+        LevelHistogram { counts }
+    }
+}
+
+impl<K: Ord, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SkipList<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    list: &'a SkipList<K, V>,
+    next: Option<usize>,
+    /// The first node not to visit, `None` to go to the end.
+    end: Option<usize>,
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.next.filter(|&index| Some(index) != self.end)?;
+        let node = self.list.node(index);
+        self.next = node.next[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// How many nodes reach each level of a [`SkipList`], see
+/// [`SkipList::level_histogram`]. `Display` draws it as bars, the highest
+/// level on top:
+///
+/// ```text
+/// level 3 |  2 ##
+/// level 2 |  6 ######
+/// level 1 | 10 ##########
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelHistogram {
+    /// Level 1 first, it has every node.
+    pub counts: Vec<usize>,
+}
+
+impl fmt::Display for LevelHistogram {
+    /// Bars longer than 50 are scaled down to fit.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const WIDTH: usize = 50;
+        let Some(&most) = self.counts.first() else {
+            return write!(f, "empty");
+        };
+        let level_width = self.counts.len().to_string().len();
+        let count_width = most.to_string().len();
+        let lines: Vec<String> = self
+            .counts
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(level, &count)| {
+                let bar = if most <= WIDTH {
+                    count
+                } else {
+                    (count * WIDTH).div_ceil(most)
+                };
+                format!(
+                    "level {:>lw$} | {:>cw$} {}",
+                    level + 1,
+                    count,
+                    "#".repeat(bar),
+                    lw = level_width,
+                    cw = count_width
+                )
+            })
+            .collect();
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    use super::{LevelHistogram, SkipList, SplitMix64};
+
+    fn keys<V>(iter: super::Iter<'_, i32, V>) -> Vec<i32> {
+        iter.map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn test_map() {
+        let mut list = SkipList::new();
+        assert_eq!(list.first(), None);
+        assert_eq!(list.last(), None);
+        assert_eq!(list.remove(&1), None);
+        for key in [30, 10, 50, 20, 40] {
+            assert_eq!(list.insert(key, key.to_string()), None);
+        }
+        assert_eq!(list.insert(20, "twenty".into()), Some("20".into()));
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.get(&20).map(String::as_str), Some("twenty"));
+        assert_eq!(list.get(&25), None);
+        list.get_mut(&10).unwrap().push('!');
+        assert_eq!(list.first(), Some((&10, &"10!".to_owned())));
+        assert_eq!(list.last(), Some((&50, &"50".to_owned())));
+        assert_eq!(list.remove(&50), Some("50".into()));
+        assert_eq!(list.remove(&50), None);
+        assert_eq!(list.last().map(|(key, _)| *key), Some(40));
+        assert!(list.contains_key(&30));
+        assert_eq!(keys(list.iter()), vec![10, 20, 30, 40]);
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(list.iter().next(), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let list: SkipList<i32, ()> = (0..20).map(|key| (key * 5, ())).collect();
+        assert_eq!(keys(list.range(10..25)), vec![10, 15, 20]);
+        assert_eq!(keys(list.range(11..=25)), vec![15, 20, 25]);
+        assert_eq!(keys(list.range(..=7)), vec![0, 5]);
+        assert_eq!(keys(list.range(90..)), vec![90, 95]);
+        assert_eq!(
+            keys(list.range((Bound::Excluded(90), Bound::Unbounded))),
+            vec![95]
+        );
+        assert_eq!(list.range(96..).next(), None);
+        assert_eq!(list.range(11..14).next(), None);
+        assert_eq!(
+            list.range((Bound::Excluded(50), Bound::Excluded(45)))
+                .next(),
+            None
+        );
+        assert_eq!(list.range(..).count(), 20);
+        // keys can be looked up by what they borrow as
+        let words: SkipList<String, usize> = ["b", "d", "a", "c"]
+            .iter()
+            .map(|w| (w.to_string(), w.len()))
+            .collect();
+        let found: Vec<_> = words
+            .range::<str, _>((Bound::Included("b"), Bound::Excluded("d")))
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(found, vec!["b", "c"]);
+        assert_eq!(words.get("c"), Some(&1));
+    }
+
+    #[test]
+    fn test_against_btree_map() {
+        let mut rng = SplitMix64 { state: 7 };
+        let mut list = SkipList::with_seed(42);
+        let mut map = BTreeMap::new();
+        for _ in 0..5_000 {
+            let key = (rng.next() % 500) as i32;
+            if rng.next().is_multiple_of(3) {
+                assert_eq!(list.remove(&key), map.remove(&key));
+            } else {
+                assert_eq!(list.insert(key, key * 2), map.insert(key, key * 2));
+            }
+        }
+        assert_eq!(list.len(), map.len());
+        assert!(list.iter().eq(map.iter()));
+        assert!(list.range(100..200).eq(map.range(100..200)));
+        assert_eq!(list.first(), map.first_key_value());
+        assert_eq!(list.last(), map.last_key_value());
+        // removed slots are reused
+        assert!(list.nodes.len() <= 500);
+    }
+
+    #[test]
+    fn test_levels() {
+        let list: SkipList<i32, ()> = (0..1_000).map(|key| (key, ())).collect();
+        let counts = list.level_histogram().counts;
+        assert_eq!(counts[0], 1_000);
+        assert!(counts.windows(2).all(|pair| pair[0] >= pair[1]));
+        // about half of the nodes reach level 2
+        assert!((400..600).contains(&counts[1]), "{:?}", counts);
+        // the same seed builds the same list, another seed doesn't
+        let same: SkipList<i32, ()> = (0..1_000).map(|key| (key, ())).collect();
+        assert_eq!(same.level_histogram().counts, counts);
+        let mut other = SkipList::with_seed(1);
+        other.extend((0..1_000).map(|key| (key, ())));
+        assert_ne!(other.level_histogram().counts, counts);
+    }
+
+    #[test]
+    fn test_histogram_display() {
+        let histogram = LevelHistogram {
+            counts: vec![10, 6, 2],
+        };
+        let expected = "\
+level 3 |  2 ##
+level 2 |  6 ######
+level 1 | 10 ##########";
+        assert_eq!(histogram.to_string(), expected);
+        let scaled = LevelHistogram {
+            counts: vec![200, 100, 1],
+        };
+        assert_eq!(
+            scaled.to_string().lines().map(str::len).collect::<Vec<_>>(),
+            vec![15, 39, 64]
+        );
+        assert_eq!(LevelHistogram { counts: vec![] }.to_string(), "empty");
+    }
+}
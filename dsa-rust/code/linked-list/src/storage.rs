@@ -0,0 +1,346 @@
+//! Writing a list to a file and reading it back, into the same or any
+//! other kind of list.
+//!
+//! | format | looks like                               | needs           |
+//! |--------|------------------------------------------|-----------------|
+//! | text   | one value per line                       |                 |
+//! | JSON   | `[1, 2, 3]`                              | `serde` feature |
+//! | CSV    | a `value` header, then one row per value | `serde` feature |
+//!
+//! Text can't hold a value with a line break in it, JSON can't hold a
+//! float that isn't finite, it writes them as `null`.
+
+use std::{
+    error, fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{Element, LinkedListT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+impl Format {
+    /// Picks the format by the extension of `path`, text for anything but
+    /// `.json` and `.csv`.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str());
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("json") => Self::Json,
+            Some("csv") => Self::Csv,
+            _ => Self::Text,
+        }
+    }
+    /// Whether this build can read and write the format, JSON and CSV need
+    /// the `serde` feature.
+    pub fn is_supported(self) -> bool {
+        self == Self::Text || cfg!(feature = "serde")
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Text => "text",
+            Self::Json => "JSON",
+            Self::Csv => "CSV",
+        })
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" | "txt" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!(
+                "unknown format '{}', expected text, json or csv",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    /// A line of a text file that isn't a value of the element type.
+    Parse {
+        line: usize,
+        value: String,
+        expected: &'static str,
+    },
+    /// What `serde_json` or `csv` had to say about a file.
+    Invalid(String),
+    /// JSON or CSV without the `serde` feature.
+    Unsupported(Format),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Parse {
+                line,
+                value,
+                expected,
+            } => write!(
+                f,
+                "line {}: invalid value '{}', expected {}",
+                line, value, expected
+            ),
+            Self::Invalid(message) => f.write_str(message),
+            Self::Unsupported(format) => write!(
+                f,
+                "{} files need the serde feature, build with --features serde",
+                format
+            ),
+        }
+    }
+}
+
+impl error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Writes the elements of `list` to `writer`, from the front.
+pub fn save<U, T>(list: &T, format: Format, mut writer: impl Write) -> Result<(), StorageError>
+where
+    U: Element,
+    T: LinkedListT<Item = U>,
+{
+    let values = list.to_vec();
+    match format {
+        Format::Text => {
+            for value in &values {
+                writeln!(writer, "{}", value)?;
+            }
+        }
+        #[cfg(feature = "serde")]
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut writer, &values)
+                .map_err(|error| StorageError::Invalid(error.to_string()))?;
+            writeln!(writer)?;
+        }
+        #[cfg(feature = "serde")]
+        Format::Csv => {
+            let mut csv = csv::Writer::from_writer(&mut writer);
+            let invalid = |error: csv::Error| StorageError::Invalid(error.to_string());
+            csv.write_record(["value"]).map_err(invalid)?;
+            for value in &values {
+                csv.serialize(value).map_err(invalid)?;
+            }
+            csv.flush()?;
+        }
+        #[cfg(not(feature = "serde"))]
+        format => return Err(StorageError::Unsupported(format)),
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Saves `list` to the file at `path`. The list goes into a temporary file
+/// next to it first, which then replaces `path`, so a save that fails
+/// leaves what was there before untouched.
+pub fn save_file<U, T>(list: &T, format: Format, path: impl AsRef<Path>) -> Result<(), StorageError>
+where
+    U: Element,
+    T: LinkedListT<Item = U>,
+{
+    if !format.is_supported() {
+        return Err(StorageError::Unsupported(format));
+    }
+    let path = path.as_ref();
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let saved = File::create(&temporary)
+        .map_err(StorageError::from)
+        .and_then(|file| save(list, format, BufWriter::new(file)))
+        .and_then(|()| Ok(fs::rename(&temporary, path)?));
+    if saved.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    saved
+}
+
+/// Reads the values `save` wrote, nothing is returned unless all of them
+/// could be read.
+pub fn read_values<U: Element>(format: Format, reader: impl Read) -> Result<Vec<U>, StorageError> {
+    match format {
+        Format::Text => {
+            let mut values = Vec::new();
+            for (number, line) in BufReader::new(reader).lines().enumerate() {
+                let line = line?;
+                match line.parse() {
+                    Ok(value) => values.push(value),
+                    Err(_) => {
+                        return Err(StorageError::Parse {
+                            line: number + 1,
+                            value: line,
+                            expected: U::NAME,
+                        })
+                    }
+                }
+            }
+            Ok(values)
+        }
+        #[cfg(feature = "serde")]
+        Format::Json => serde_json::from_reader(reader)
+            .map_err(|error| StorageError::Invalid(error.to_string())),
+        #[cfg(feature = "serde")]
+        Format::Csv => csv::Reader::from_reader(reader)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|error| StorageError::Invalid(error.to_string())),
+        #[cfg(not(feature = "serde"))]
+        format => Err(StorageError::Unsupported(format)),
+    }
+}
+
+/// Reads what `save` wrote into a new list of any kind.
+pub fn load<U, T>(format: Format, reader: impl Read) -> Result<T, StorageError>
+where
+    U: Element,
+    T: LinkedListT<Item = U>,
+{
+    let mut list = T::new();
+    for value in read_values(format, reader)? {
+        list.push_back(value);
+    }
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use super::{load, save, Format, StorageError};
+    use crate::{
+        ArenaLinkedList, BoxSingleLinkedList, CircularLinkedList, Element, LinkedListT,
+        RawDoubleLinkedList, RcDoubleLinkedList, RcSingleLinkedList,
+    };
+
+    fn formats() -> Vec<Format> {
+        if cfg!(feature = "serde") {
+            vec![Format::Text, Format::Json, Format::Csv]
+        } else {
+            vec![Format::Text]
+        }
+    }
+
+    /// Saves `values` from one kind of list and loads them into another.
+    fn round_trip<U, From, To>(values: &[U])
+    where
+        U: Element + Debug,
+        From: LinkedListT<Item = U>,
+        To: LinkedListT<Item = U>,
+    {
+        let mut from = From::new();
+        for value in values {
+            from.push_back(value.clone());
+        }
+        for format in formats() {
+            let mut file = Vec::new();
+            save(&from, format, &mut file).unwrap();
+            let to: To = load(format, file.as_slice()).unwrap();
+            assert_eq!(to.to_vec(), values, "{} round trip", format);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip::<i32, BoxSingleLinkedList<i32>, ArenaLinkedList<i32>>(&[3, -1, 4]);
+        round_trip::<i32, RcSingleLinkedList<i32>, BoxSingleLinkedList<i32>>(&[]);
+        round_trip::<i64, RcDoubleLinkedList<i64>, CircularLinkedList<i64>>(&[i64::MIN, 0]);
+        round_trip::<f64, RawDoubleLinkedList<f64>, RcSingleLinkedList<f64>>(&[0.1, -2.5e-7]);
+        round_trip::<char, ArenaLinkedList<char>, RcDoubleLinkedList<char>>(&['a', ',', 'ß']);
+        round_trip::<String, CircularLinkedList<String>, RawDoubleLinkedList<String>>(&[
+            "plain".into(),
+            String::new(),
+            "with, comma".into(),
+            "\"quoted\"".into(),
+            " spaces ".into(),
+        ]);
+    }
+
+    #[test]
+    fn test_text() {
+        let list: BoxSingleLinkedList<i32> = (1..=3).collect();
+        let mut file = Vec::new();
+        save(&list, Format::Text, &mut file).unwrap();
+        assert_eq!(file, b"1\n2\n3\n");
+        let error =
+            load::<i32, BoxSingleLinkedList<i32>>(Format::Text, &b"1\ntwo\n3\n"[..]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: invalid value 'two', expected i32"
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Format::from_path("list.JSON"), Format::Json);
+        assert_eq!(Format::from_path("dir/list.csv"), Format::Csv);
+        assert_eq!(Format::from_path("list"), Format::Text);
+        assert_eq!("txt".parse(), Ok(Format::Text));
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_csv() {
+        let list: BoxSingleLinkedList<String> =
+            ["a", "b,c"].map(String::from).into_iter().collect();
+        let mut json = Vec::new();
+        save(&list, Format::Json, &mut json).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "[\n  \"a\",\n  \"b,c\"\n]\n"
+        );
+        let mut csv = Vec::new();
+        save(&list, Format::Csv, &mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "value\na\n\"b,c\"\n");
+
+        let error = load::<i32, BoxSingleLinkedList<i32>>(Format::Json, &b"[1, \"2\"]"[..]);
+        assert!(matches!(error, Err(StorageError::Invalid(_))));
+        let error = load::<char, BoxSingleLinkedList<char>>(Format::Csv, &b"value\nab\n"[..]);
+        assert!(matches!(error, Err(StorageError::Invalid(_))));
+    }
+
+    #[cfg(not(feature = "serde"))]
+    #[test]
+    fn test_without_serde() {
+        let list = BoxSingleLinkedList::<i32>::new();
+        let error = save(&list, Format::Json, Vec::new()).unwrap_err();
+        assert!(matches!(error, StorageError::Unsupported(Format::Json)));
+        assert_eq!(
+            error.to_string(),
+            "JSON files need the serde feature, build with --features serde"
+        );
+    }
+}
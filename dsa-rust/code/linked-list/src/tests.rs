@@ -0,0 +1,386 @@
+//! Scenarios every `LinkedListT` implementation has to pass.
+
+use crate::{run_script, LinkedListT, Options, ScriptStatus};
+
+/// Lets the shared scenarios look inside a list, like `to_vec` but also
+/// checking that the links of the list agree with each other.
+pub trait Snapshot: LinkedListT {
+    fn snapshot(&self) -> Vec<Self::Item>;
+}
+
+pub fn push_front<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    assert!(list.snapshot().is_empty());
+    list.push_front(3);
+    list.push_front(2);
+    list.push_front(1);
+    assert_eq!(list.snapshot(), vec![1, 2, 3]);
+}
+
+pub fn push_back<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    list.push_back(1);
+    list.push_back(2);
+    list.push_front(0);
+    list.push_back(3);
+    assert_eq!(list.snapshot(), vec![0, 1, 2, 3]);
+}
+
+pub fn remove<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    assert!(!list.remove(&1));
+    for i in 1..=5 {
+        list.push_back(i);
+    }
+    assert!(list.remove(&1));
+    assert_eq!(list.snapshot(), vec![2, 3, 4, 5]);
+    assert!(list.remove(&3));
+    assert_eq!(list.snapshot(), vec![2, 4, 5]);
+    assert!(list.remove(&5));
+    assert_eq!(list.snapshot(), vec![2, 4]);
+    assert!(!list.remove(&9));
+    assert_eq!(list.snapshot(), vec![2, 4]);
+    // the tail has to follow removals at the end
+    list.push_back(6);
+    assert_eq!(list.snapshot(), vec![2, 4, 6]);
+    list.remove(&2);
+    list.remove(&4);
+    list.remove(&6);
+    assert!(list.snapshot().is_empty());
+    assert_eq!(list.len(), 0);
+    list.push_back(7);
+    list.push_front(8);
+    assert_eq!(list.snapshot(), vec![8, 7]);
+}
+
+pub fn remove_first_match<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    for i in [1, 2, 1, 2] {
+        list.push_back(i);
+    }
+    list.remove(&2);
+    assert_eq!(list.snapshot(), vec![1, 1, 2]);
+}
+
+pub fn update<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    assert_eq!(list.update(&1, 2), None);
+    assert!(list.snapshot().is_empty());
+    for i in [1, 2, 3, 2] {
+        list.push_back(i);
+    }
+    assert_eq!(list.update(&2, 20), Some(2));
+    assert_eq!(list.snapshot(), vec![1, 20, 3, 2]);
+    assert_eq!(list.update(&2, 30), Some(2));
+    assert_eq!(list.update(&9, 90), None);
+    assert_eq!(list.snapshot(), vec![1, 20, 3, 30]);
+}
+
+pub fn len_contains<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    assert!(list.is_empty());
+    assert!(!list.contains(&1));
+    for i in 1..=3 {
+        list.push_back(i);
+    }
+    assert_eq!(list.len(), 3);
+    assert!(!list.is_empty());
+    assert!(list.contains(&1));
+    assert!(list.contains(&3));
+    assert!(!list.contains(&4));
+}
+
+pub fn get<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    assert_eq!(list.get(0), None);
+    for i in 0..5 {
+        list.push_back(i * 10);
+    }
+    for i in 0..5 {
+        assert_eq!(list.get(i), Some(i as i32 * 10));
+    }
+    assert_eq!(list.get(5), None);
+}
+
+pub fn insert_remove_at<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    list.insert_at(0, 2);
+    list.insert_at(0, 0);
+    list.insert_at(1, 1);
+    list.insert_at(3, 4);
+    list.insert_at(3, 3);
+    assert_eq!(list.snapshot(), vec![0, 1, 2, 3, 4]);
+    assert_eq!(list.len(), 5);
+    assert_eq!(list.remove_at(5), None);
+    assert_eq!(list.remove_at(2), Some(2));
+    assert_eq!(list.remove_at(3), Some(4));
+    assert_eq!(list.remove_at(0), Some(0));
+    assert_eq!(list.snapshot(), vec![1, 3]);
+    // the tail has to follow removals at the end
+    list.push_back(5);
+    assert_eq!(list.snapshot(), vec![1, 3, 5]);
+    assert_eq!(list.len(), 3);
+}
+
+pub fn insert_at_out_of_bounds<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    list.push_back(1);
+    list.insert_at(2, 2);
+}
+
+pub fn pop_front_back<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    assert_eq!(list.pop_front(), None);
+    assert_eq!(list.pop_back(), None);
+    for i in 1..=4 {
+        list.push_back(i);
+    }
+    assert_eq!(list.pop_front(), Some(1));
+    assert_eq!(list.pop_back(), Some(4));
+    assert_eq!(list.snapshot(), vec![2, 3]);
+    assert_eq!(list.pop_back(), Some(3));
+    assert_eq!(list.pop_back(), Some(2));
+    assert_eq!(list.pop_front(), None);
+    assert!(list.is_empty());
+    list.push_back(5);
+    list.push_front(6);
+    assert_eq!(list.snapshot(), vec![6, 5]);
+}
+
+pub fn clear<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    for i in 1..=3 {
+        list.push_back(i);
+    }
+    list.clear();
+    assert!(list.is_empty());
+    assert!(list.snapshot().is_empty());
+    list.push_back(1);
+    assert_eq!(list.snapshot(), vec![1]);
+}
+
+/// `end` is what the list prints after its last element.
+pub fn display<L: Snapshot<Item = i32>>(end: &str) {
+    let mut list = L::new();
+    let empty = list.to_string();
+    assert!(empty.ends_with("None"));
+    list.push_back(1);
+    list.push_back(2);
+    let text = list.to_string();
+    assert!(text.contains('1') && text.contains('2') && text.ends_with(end));
+    assert!(text.find('1') < text.find('2'));
+}
+
+pub fn sort<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    list.sort();
+    assert!(list.snapshot().is_empty());
+    for i in [3, 1, 4, 1, 5, 9, 2, 6] {
+        list.push_back(i);
+    }
+    list.sort();
+    assert_eq!(list.snapshot(), vec![1, 1, 2, 3, 4, 5, 6, 9]);
+    assert_eq!(list.len(), 8);
+    // the tail has to end up on the new last node
+    list.push_back(0);
+    assert_eq!(list.pop_back(), Some(0));
+    assert_eq!(list.pop_back(), Some(9));
+}
+
+/// Every list prints the same for a script that doesn't print the list.
+pub fn script<L: Snapshot<Item = i32>>() {
+    let script = "push_back 2\npush_front 1\ninsert_at 2 3\nget 2\nupdate 2 9\n\
+                  remove 4\nsort\npop_back\nremove_at 0\nlen\npop_front\npop_front\n";
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    let status =
+        run_script::<i32, L>(script.as_bytes(), &mut out, &mut err, &Options::default()).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "Value at 2: 3\nPopped 9.\nRemoved 1.\nLength: 1\nPopped 3.\n"
+    );
+    assert_eq!(
+        String::from_utf8(err).unwrap(),
+        "line 6: Value 4 not found.\nline 12: List is empty.\n"
+    );
+    assert_eq!(status, ScriptStatus::Failed);
+}
+
+/// The nodes are listed from the front and linked in that order, the
+/// last one links to nothing or, in a circular list, to the first one. A
+/// node holding several values lists them separated by commas.
+pub fn layout<L: Snapshot<Item = i32>>() {
+    let mut list = L::new();
+    assert!(list.layout().nodes.is_empty());
+    for i in 1..=3 {
+        list.push_back(i);
+    }
+    list.remove(&2);
+    list.push_back(4);
+    let layout = list.layout();
+    let nodes = &layout.nodes;
+    let values: Vec<&str> = nodes
+        .iter()
+        .flat_map(|node| node.value.split(", "))
+        .collect();
+    assert_eq!(values, ["1", "3", "4"]);
+    for (index, node) in nodes.iter().enumerate() {
+        match nodes.get(index + 1) {
+            Some(next) => assert_eq!(node.next.unwrap().target, next.location),
+            None => assert!(node
+                .next
+                .is_none_or(|next| next.target == nodes[0].location)),
+        }
+        if let Some(prev) = node.prev {
+            assert_eq!(prev.target, nodes[index - 1].location);
+        }
+    }
+    for root in &layout.roots {
+        let expected = match root.name {
+            "head" => &nodes[0],
+            _ => nodes.last().unwrap(),
+        };
+        assert_eq!(root.link.unwrap().target, expected.location);
+    }
+    assert_eq!(layout.to_dot().matches("[label=\"{#").count(), nodes.len());
+}
+
+macro_rules! linked_list_tests {
+    ($name:ident, $list:ty) => {
+        linked_list_tests!($name, $list, "None");
+    };
+    ($name:ident, $list:ty, $end:literal) => {
+        mod $name {
+            use crate::tests;
+
+            #[test]
+            fn push_front() {
+                tests::push_front::<$list>();
+            }
+            #[test]
+            fn push_back() {
+                tests::push_back::<$list>();
+            }
+            #[test]
+            fn remove() {
+                tests::remove::<$list>();
+            }
+            #[test]
+            fn remove_first_match() {
+                tests::remove_first_match::<$list>();
+            }
+            #[test]
+            fn update() {
+                tests::update::<$list>();
+            }
+            #[test]
+            fn len_contains() {
+                tests::len_contains::<$list>();
+            }
+            #[test]
+            fn get() {
+                tests::get::<$list>();
+            }
+            #[test]
+            fn insert_remove_at() {
+                tests::insert_remove_at::<$list>();
+            }
+            #[test]
+            #[should_panic]
+            fn insert_at_out_of_bounds() {
+                tests::insert_at_out_of_bounds::<$list>();
+            }
+            #[test]
+            fn pop_front_back() {
+                tests::pop_front_back::<$list>();
+            }
+            #[test]
+            fn clear() {
+                tests::clear::<$list>();
+            }
+            #[test]
+            fn display() {
+                tests::display::<$list>($end);
+            }
+            #[test]
+            fn sort() {
+                tests::sort::<$list>();
+            }
+            #[test]
+            fn script() {
+                tests::script::<$list>();
+            }
+            #[test]
+            fn layout() {
+                tests::layout::<$list>();
+            }
+        }
+    };
+}
+
+linked_list_tests!(box_single, crate::BoxSingleLinkedList<i32>);
+linked_list_tests!(rc_single, crate::RcSingleLinkedList<i32>);
+linked_list_tests!(rc_double, crate::RcDoubleLinkedList<i32>);
+linked_list_tests!(raw_double, crate::RawDoubleLinkedList<i32>);
+linked_list_tests!(arena, crate::ArenaLinkedList<i32>);
+linked_list_tests!(circular, crate::CircularLinkedList<i32>, "back to 1");
+linked_list_tests!(unrolled, crate::UnrolledLinkedList<i32>);
+linked_list_tests!(unrolled_pairs, crate::UnrolledLinkedList<i32, 2>);
+
+/// Records every value the list reports as dropped.
+pub fn drop_observer<L: Snapshot<Item = i32>>() {
+    use std::sync::{Arc, Mutex};
+
+    let dropped = Arc::new(Mutex::new(Vec::new()));
+    let mut list = L::new();
+    let log = Arc::clone(&dropped);
+    list.set_drop_observer(Box::new(move |v| log.lock().unwrap().push(*v)));
+    for i in 1..=4 {
+        list.push_back(i);
+    }
+    list.remove(&3);
+    list.remove(&1);
+    list.remove(&9);
+    // popped values go back to the caller, they aren't dropped
+    assert_eq!(list.pop_back(), Some(4));
+    assert_eq!(*dropped.lock().unwrap(), vec![3, 1]);
+    list.push_back(5);
+    drop(list);
+    assert_eq!(*dropped.lock().unwrap(), vec![3, 1, 2, 5]);
+}
+
+#[test]
+fn box_single_drop_observer() {
+    drop_observer::<crate::BoxSingleLinkedList<i32>>();
+}
+
+#[test]
+fn rc_single_drop_observer() {
+    drop_observer::<crate::RcSingleLinkedList<i32>>();
+}
+
+#[test]
+fn circular_drop_observer() {
+    drop_observer::<crate::CircularLinkedList<i32>>();
+}
+
+fn drop_long_list<L: LinkedListT<Item = i32>>() {
+    let mut list = L::new();
+    for i in 0..10_000_000 {
+        list.push_front(i);
+    }
+    drop(list);
+}
+
+// Dropping used to recurse once per node and overflow the stack. The
+// lists are dropped one after the other to keep the memory use down.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn drop_long_lists() {
+    drop_long_list::<crate::BoxSingleLinkedList<i32>>();
+    drop_long_list::<crate::RcSingleLinkedList<i32>>();
+    drop_long_list::<crate::RcDoubleLinkedList<i32>>();
+    drop_long_list::<crate::RawDoubleLinkedList<i32>>();
+    drop_long_list::<crate::CircularLinkedList<i32>>();
+    drop_long_list::<crate::ArenaLinkedList<i32>>();
+    drop_long_list::<crate::UnrolledLinkedList<i32>>();
+}
@@ -0,0 +1,252 @@
+//! Runs the scripts in `tests/golden` through the binary and compares what
+//! it prints with the `.stdout` and `.stderr` files next to them.
+
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+fn golden(name: &str, extension: &str) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name]
+        .iter()
+        .collect::<PathBuf>()
+        .with_extension(extension)
+}
+
+fn binary() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_linked-list"))
+}
+
+fn check(name: &str, output: Output, code: i32) {
+    let expected = |extension| fs::read_to_string(golden(name, extension)).unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        expected("stdout")
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        expected("stderr")
+    );
+    assert_eq!(output.status.code(), Some(code));
+}
+
+/// Runs the script once from a file and once piped into stdin.
+fn run(name: &str, args: &[&str], code: i32) {
+    let output = binary()
+        .args(args)
+        .arg("--script")
+        .arg(golden(name, "script"))
+        .output()
+        .unwrap();
+    check(name, output, code);
+
+    let mut child = binary()
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let script = fs::read(golden(name, "script")).unwrap();
+    child.stdin.take().unwrap().write_all(&script).unwrap();
+    check(name, child.wait_with_output().unwrap(), code);
+}
+
+#[test]
+fn basic() {
+    run("basic", &[], 0);
+}
+
+#[test]
+fn failures() {
+    run("failures", &[], 1);
+}
+
+#[test]
+fn invalid() {
+    run("invalid", &[], 2);
+}
+
+#[test]
+fn exit() {
+    run("exit", &[], 0);
+}
+
+#[test]
+fn floats() {
+    run("floats", &["--type", "f64", "--tolerance", "0.001"], 1);
+}
+
+#[test]
+fn chars() {
+    run("chars", &["--type", "char"], 2);
+}
+
+#[test]
+fn arena_layout() {
+    // slots, unlike addresses, are the same in every run
+    run("arena_layout", &["--list", "arena"], 0);
+}
+
+#[test]
+fn invalid_arguments() {
+    for (args, message) in [
+        (&["--type", "u8"][..], "unknown element type 'u8'"),
+        (&["--tolerance", "-1"], "invalid tolerance '-1'"),
+        (&["--type"], "--type needs a value"),
+        (&["--list", "vec"], "unknown list 'vec'"),
+        (&["script.txt"], "unknown argument 'script.txt'"),
+    ] {
+        let output = binary().args(args).output().unwrap();
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with(message));
+        assert_eq!(output.status.code(), Some(2));
+    }
+}
+
+#[test]
+fn missing_script() {
+    let output = binary()
+        .arg("--script")
+        .arg(golden("missing", "script"))
+        .output()
+        .unwrap();
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("cannot read"));
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn empty_stdin() {
+    let output = binary().stdin(Stdio::null()).output().unwrap();
+    assert!(output.stdout.is_empty());
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn every_list() {
+    // a script that doesn't print the list prints the same for every list
+    for list in [
+        "box",
+        "rc",
+        "rc-double",
+        "circular",
+        "raw-double",
+        "arena",
+        "unrolled",
+    ] {
+        run("failures_no_print", &["--list", list], 1);
+    }
+}
+
+/// Pipes `script` into the binary running in `dir`, so the files it saves
+/// end up there.
+fn pipe(dir: &Path, args: &[&str], script: &str) -> (String, String, Option<i32>) {
+    let mut child = binary()
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+        output.status.code(),
+    )
+}
+
+#[test]
+fn save_and_load() {
+    let dir = env::temp_dir().join(format!("linked-list-save-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut files = vec!["list.txt"];
+    if cfg!(feature = "serde") {
+        files.extend(["list.json", "list.csv"]);
+    }
+    for file in files {
+        let saved = pipe(
+            &dir,
+            &["--list", "box"],
+            &format!("push_back 1\npush_back 2\nsave {}\n", file),
+        );
+        assert_eq!(
+            saved,
+            (
+                format!("Saved 2 values to {}.\n", file),
+                String::new(),
+                Some(0)
+            )
+        );
+        // into another kind of list, replacing what was in it
+        let loaded = pipe(
+            &dir,
+            &["--list", "arena"],
+            &format!("push_back 9\nload {}\nget 1\nlen\n", file),
+        );
+        assert_eq!(
+            loaded,
+            (
+                format!("Loaded 2 values from {}.\nValue at 1: 2\nLength: 2\n", file),
+                String::new(),
+                Some(0)
+            )
+        );
+    }
+    let missing = pipe(&dir, &[], "load missing.txt\n");
+    assert!(missing.1.starts_with("line 1: Cannot load missing.txt:"));
+    assert_eq!(missing.2, Some(1));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failed_save_keeps_the_file() {
+    let dir = env::temp_dir().join(format!("linked-list-keep-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("x.json");
+    fs::write(&file, "keep me\n").unwrap();
+    let saved = pipe(&dir, &[], "push_back 1\nsave x.json\n");
+    if cfg!(feature = "serde") {
+        assert_eq!(saved.2, Some(0));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "[\n  1\n]\n");
+    } else {
+        assert_eq!(
+            saved.1,
+            "line 2: Cannot save to x.json: JSON files need the serde feature, \
+             build with --features serde\n"
+        );
+        assert_eq!(saved.2, Some(1));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "keep me\n");
+    }
+    assert!(!dir.join("x.json.tmp").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn help() {
+    let output = binary().arg("--help").output().unwrap();
+    let help = String::from_utf8(output.stdout).unwrap();
+    for operation in [
+        "push_front",
+        "insert_at",
+        "sort",
+        "--no-clear",
+        "raw-double",
+    ] {
+        assert!(help.contains(operation), "{} is missing", operation);
+    }
+    assert_eq!(output.status.code(), Some(0));
+}
@@ -1,5 +1,12 @@
+// unfinished exercises, kept as they are
+#[allow(dead_code, unused, clippy::while_let_on_iterator)]
 mod notation;
+mod postfix;
+pub mod recursion;
+mod stack;
 
-struct Stack<T, const SIZE: usize> {
-    elements: [T; SIZE],
-}
+pub use postfix::{
+    evaluate_postfix, run_dc, EvalError, EvalErrorKind, Operator, PostfixEvaluator, Token,
+    Tokenizer,
+};
+pub use stack::{CapacityError, Drain, IntoIter, Iter, Stack};
@@ -0,0 +1,32 @@
+use std::io;
+
+use stack::{evaluate_postfix, run_dc};
+
+/// Maximum number of operands the calculator can hold.
+const STACK_SIZE: usize = 1024;
+
+fn main() {
+    let stdin = io::stdin().lock();
+    match std::env::args().nth(1).as_deref() {
+        // evaluate the whole input as a single postfix expression
+        Some("-e") | Some("--eval") => match evaluate_postfix::<_, STACK_SIZE>(stdin) {
+            Ok(value) => println!("{}", value),
+            Err(err) => {
+                eprintln!("rpn: {}", err);
+                std::process::exit(1);
+            }
+        },
+        None => match run_dc::<_, _, _, STACK_SIZE>(stdin, io::stdout(), io::stderr()) {
+            Ok(0) => {}
+            Ok(_) => std::process::exit(1),
+            Err(err) => {
+                eprintln!("dc: {}", err);
+                std::process::exit(2);
+            }
+        },
+        Some(_) => {
+            eprintln!("Usage: stack [-e | --eval] < input");
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use std::{
+    error, fmt,
+    io::{self, BufRead, Write},
+};
+
+use crate::Stack;
+
+/// Longest token the tokenizer will buffer, anything longer is rejected
+/// so a malformed input can't make us grow without bound.
+const MAX_TOKEN_LEN: usize = 256;
+
+#[derive(Debug)]
+pub enum EvalErrorKind {
+    Io(io::Error),
+    InvalidToken(String),
+    TokenTooLong,
+    StackUnderflow,
+    StackOverflow,
+    DivisionByZero,
+    EmptyExpression,
+    /// The input ended with more than one value left on the stack.
+    UnusedOperands(usize),
+}
+
+impl fmt::Display for EvalErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::InvalidToken(token) => write!(f, "invalid token `{}`", token),
+            Self::TokenTooLong => write!(f, "token longer than {} bytes", MAX_TOKEN_LEN),
+            Self::StackUnderflow => f.write_str("stack underflow"),
+            Self::StackOverflow => f.write_str("stack overflow"),
+            Self::DivisionByZero => f.write_str("division by zero"),
+            Self::EmptyExpression => f.write_str("empty expression"),
+            Self::UnusedOperands(n) => write!(f, "{} operands left on the stack", n),
+        }
+    }
+}
+
+/// An evaluation error together with the (1 based) position it happened at.
+#[derive(Debug)]
+pub struct EvalError {
+    pub kind: EvalErrorKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl error::Error for EvalError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            EvalErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percentage,
+    Caret,
+}
+
+impl Operator {
+    fn apply(self, left: f64, right: f64) -> Result<f64, EvalErrorKind> {
+        match self {
+            Self::Plus => Ok(left + right),
+            Self::Minus => Ok(left - right),
+            Self::Star => Ok(left * right),
+            Self::Slash | Self::Percentage if right == 0.0 => Err(EvalErrorKind::DivisionByZero),
+            Self::Slash => Ok(left / right),
+            Self::Percentage => Ok(left % right),
+            Self::Caret => Ok(left.powf(right)),
+        }
+    }
+}
+
+impl TryFrom<&str> for Operator {
+    type Error = ();
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "+" => Ok(Self::Plus),
+            "-" => Ok(Self::Minus),
+            "*" => Ok(Self::Star),
+            "/" => Ok(Self::Slash),
+            "%" => Ok(Self::Percentage),
+            "^" => Ok(Self::Caret),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A whitespace separated token and the position of its first byte.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Splits any `BufRead` into whitespace separated tokens without reading
+/// the whole input into memory.
+pub struct Tokenizer<R: BufRead> {
+    reader: R,
+    line: usize,
+    column: usize,
+}
+
+impl<R: BufRead> Tokenizer<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: 1,
+            column: 1,
+        }
+    }
+    fn error(&self, kind: EvalErrorKind) -> EvalError {
+        EvalError {
+            kind,
+            line: self.line,
+            column: self.column,
+        }
+    }
+    pub fn next_token(&mut self) -> Result<Option<Token>, EvalError> {
+        let mut bytes = Vec::new();
+        let (mut line, mut column) = (self.line, self.column);
+        let mut too_long = false;
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(self.error(EvalErrorKind::Io(err))),
+            };
+            if buf.is_empty() {
+                break;
+            }
+            let mut used = 0;
+            let mut done = false;
+            for &byte in buf {
+                if byte.is_ascii_whitespace() {
+                    if !bytes.is_empty() {
+                        done = true;
+                        break;
+                    }
+                    used += 1;
+                    if byte == b'\n' {
+                        self.line += 1;
+                        self.column = 1;
+                    } else {
+                        self.column += 1;
+                    }
+                    continue;
+                }
+                if bytes.is_empty() {
+                    (line, column) = (self.line, self.column);
+                }
+                // the rest of an overlong token is skipped, not read as
+                // the next token
+                if bytes.len() == MAX_TOKEN_LEN {
+                    too_long = true;
+                } else {
+                    bytes.push(byte);
+                }
+                used += 1;
+                self.column += 1;
+            }
+            self.reader.consume(used);
+            if done {
+                break;
+            }
+        }
+        if too_long {
+            return Err(EvalError {
+                kind: EvalErrorKind::TokenTooLong,
+                line,
+                column,
+            });
+        }
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        Ok(Some(Token { text, line, column }))
+    }
+}
+
+impl<R: BufRead> Iterator for Tokenizer<R> {
+    type Item = Result<Token, EvalError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token().transpose()
+    }
+}
+
+/// Evaluates postfix (RPN) input token by token.
+///
+/// Only the current token and the operand stack are kept in memory, so
+/// `SIZE` bounds the memory used no matter how long the input is.
+pub struct PostfixEvaluator<const SIZE: usize> {
+    stack: Stack<f64, SIZE>,
+}
+
+impl<const SIZE: usize> PostfixEvaluator<SIZE> {
+    pub fn new() -> Self {
+        Self {
+            stack: Stack::new(),
+        }
+    }
+    pub fn stack(&self) -> &Stack<f64, SIZE> {
+        &self.stack
+    }
+    pub fn clear(&mut self) {
+        self.stack.clear();
+    }
+    fn pop(&mut self) -> Result<f64, EvalErrorKind> {
+        self.stack.pop().ok_or(EvalErrorKind::StackUnderflow)
+    }
+    fn push(&mut self, value: f64) -> Result<(), EvalErrorKind> {
+        self.stack
+            .push(value)
+            .map_err(|_| EvalErrorKind::StackOverflow)
+    }
+    fn apply(&mut self, op: Operator) -> Result<(), EvalErrorKind> {
+        if self.stack.len() < 2 {
+            return Err(EvalErrorKind::StackUnderflow);
+        }
+        let right = self.pop()?;
+        let left = self.pop()?;
+        self.push(op.apply(left, right)?)
+    }
+    /// Pushes a number or applies an operator.
+    pub fn feed(&mut self, token: &str) -> Result<(), EvalErrorKind> {
+        if let Ok(op) = Operator::try_from(token) {
+            return self.apply(op);
+        }
+        match token.parse::<f64>() {
+            Ok(value) => self.push(value),
+            Err(_) => Err(EvalErrorKind::InvalidToken(token.to_owned())),
+        }
+    }
+    /// Evaluates the whole input, which must reduce to exactly one value.
+    pub fn evaluate<R: BufRead>(&mut self, reader: R) -> Result<f64, EvalError> {
+        let mut tokenizer = Tokenizer::new(reader);
+        while let Some(token) = tokenizer.next_token()? {
+            self.feed(&token.text).map_err(|kind| EvalError {
+                kind,
+                line: token.line,
+                column: token.column,
+            })?;
+        }
+        let kind = match self.stack.len() {
+            0 => EvalErrorKind::EmptyExpression,
+            1 => return Ok(self.stack.pop().unwrap()),
+            n => EvalErrorKind::UnusedOperands(n),
+        };
+        Err(tokenizer.error(kind))
+    }
+}
+
+impl<const SIZE: usize> Default for PostfixEvaluator<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Evaluates a postfix expression read from `reader`.
+pub fn evaluate_postfix<R: BufRead, const SIZE: usize>(reader: R) -> Result<f64, EvalError> {
+    PostfixEvaluator::<SIZE>::new().evaluate(reader)
+}
+
+/// A small `dc` like calculator.
+///
+/// Besides numbers and operators it understands these commands:
+///
+/// - `p` print the top of the stack
+/// - `n` pop and print the top of the stack
+/// - `f` print the whole stack, top first
+/// - `d` duplicate the top of the stack
+/// - `r` swap the top two elements
+/// - `c` clear the stack
+/// - `q` quit
+///
+/// Errors are written to `err` and evaluation carries on with the next
+/// token, like `dc` does. Returns the number of errors reported.
+pub fn run_dc<R, W, E, const SIZE: usize>(reader: R, mut out: W, mut err: E) -> io::Result<usize>
+where
+    R: BufRead,
+    W: Write,
+    E: Write,
+{
+    let mut evaluator = PostfixEvaluator::<SIZE>::new();
+    let mut tokenizer = Tokenizer::new(reader);
+    let mut errors = 0;
+    loop {
+        let token = match tokenizer.next_token() {
+            Ok(Some(token)) => token,
+            Ok(None) => break,
+            Err(EvalError {
+                kind: EvalErrorKind::Io(e),
+                ..
+            }) => return Err(e),
+            Err(e) => {
+                errors += 1;
+                writeln!(err, "dc: {}", e)?;
+                continue;
+            }
+        };
+        let stack = &mut evaluator.stack;
+        let result = match token.text.as_str() {
+            "p" => match stack.peek() {
+                Some(v) => writeln!(out, "{}", v).map_err(EvalErrorKind::Io),
+                None => Err(EvalErrorKind::StackUnderflow),
+            },
+            "n" => match stack.pop() {
+                Some(v) => writeln!(out, "{}", v).map_err(EvalErrorKind::Io),
+                None => Err(EvalErrorKind::StackUnderflow),
+            },
+            "f" => stack
+                .iter()
+                .try_for_each(|v| writeln!(out, "{}", v))
+                .map_err(EvalErrorKind::Io),
+            "d" => match stack.peek() {
+                Some(&v) => stack.push(v).map_err(|_| EvalErrorKind::StackOverflow),
+                None => Err(EvalErrorKind::StackUnderflow),
+            },
+            "r" => {
+                if stack.len() < 2 {
+                    Err(EvalErrorKind::StackUnderflow)
+                } else {
+                    let a = stack.pop().unwrap();
+                    let b = stack.pop().unwrap();
+                    stack.push(a).unwrap();
+                    stack.push(b).unwrap();
+                    Ok(())
+                }
+            }
+            "c" => {
+                stack.clear();
+                Ok(())
+            }
+            "q" => break,
+            text => evaluator.feed(text),
+        };
+        match result {
+            Ok(()) => {}
+            Err(EvalErrorKind::Io(e)) => return Err(e),
+            Err(kind) => {
+                errors += 1;
+                let e = EvalError {
+                    kind,
+                    line: token.line,
+                    column: token.column,
+                };
+                writeln!(err, "dc: {}", e)?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Cursor};
+
+    use super::{evaluate_postfix, run_dc, EvalErrorKind, Tokenizer};
+
+    fn eval(input: &str) -> Result<f64, super::EvalError> {
+        evaluate_postfix::<_, 16>(Cursor::new(input))
+    }
+
+    #[test]
+    fn test_evaluate_postfix() {
+        assert_eq!(eval("1 2 +").unwrap(), 3.0);
+        assert_eq!(eval("5 1 2 + 4 * + 3 -").unwrap(), 14.0);
+        assert_eq!(eval("2 3 ^\n 5 %").unwrap(), 3.0);
+        assert_eq!(eval("-2.5 2 *").unwrap(), -5.0);
+    }
+
+    #[test]
+    fn test_error_position() {
+        let err = eval("1 2 +\n3 x").unwrap_err();
+        assert!(matches!(err.kind, EvalErrorKind::InvalidToken(ref t) if t == "x"));
+        assert_eq!((err.line, err.column), (2, 3));
+
+        let err = eval("1\n  +").unwrap_err();
+        assert!(matches!(err.kind, EvalErrorKind::StackUnderflow));
+        assert_eq!((err.line, err.column), (2, 3));
+
+        let err = eval("1 0 /").unwrap_err();
+        assert!(matches!(err.kind, EvalErrorKind::DivisionByZero));
+        assert!(matches!(
+            eval("1 2").unwrap_err().kind,
+            EvalErrorKind::UnusedOperands(2)
+        ));
+        assert!(matches!(
+            eval(" \n").unwrap_err().kind,
+            EvalErrorKind::EmptyExpression
+        ));
+        assert!(matches!(
+            evaluate_postfix::<_, 2>(Cursor::new("1 2 3 + +"))
+                .unwrap_err()
+                .kind,
+            EvalErrorKind::StackOverflow
+        ));
+    }
+
+    #[test]
+    fn test_tokenizer_small_buffer() {
+        // a tiny buffer forces tokens to span several `fill_buf` calls
+        let reader = BufReader::with_capacity(2, Cursor::new("12.5  300\n+"));
+        let tokens = Tokenizer::new(reader)
+            .map(|t| t.map(|t| (t.text, t.line, t.column)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                ("12.5".to_owned(), 1, 1),
+                ("300".to_owned(), 1, 7),
+                ("+".to_owned(), 2, 1)
+            ]
+        );
+        let long = "1".repeat(1000);
+        assert!(matches!(
+            eval(&long).unwrap_err().kind,
+            EvalErrorKind::TokenTooLong
+        ));
+    }
+
+    #[test]
+    fn test_dc() {
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let input = "1 2 + p d * p 4 r f c + 7 n q 8 p";
+        let errors = run_dc::<_, _, _, 8>(Cursor::new(input), &mut out, &mut err).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "3\n9\n9\n4\n7\n");
+        assert_eq!(errors, 1);
+        assert_eq!(
+            String::from_utf8(err).unwrap(),
+            "dc: 1:23: stack underflow\n"
+        );
+    }
+
+    #[test]
+    fn test_dc_token_too_long() {
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let input = format!("{}p 5 f", "1".repeat(300));
+        let errors = run_dc::<_, _, _, 8>(Cursor::new(input), &mut out, &mut err).unwrap();
+        assert_eq!(errors, 1);
+        assert_eq!(
+            String::from_utf8(err).unwrap(),
+            "dc: 1:1: token longer than 256 bytes\n"
+        );
+        // only 5 made it onto the stack
+        assert_eq!(String::from_utf8(out).unwrap(), "5\n");
+    }
+}
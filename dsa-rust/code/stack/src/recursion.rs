@@ -0,0 +1,420 @@
+//! How recursion maps onto explicit stacks.
+//!
+//! Every recursive call pushes a frame on the call stack and every return
+//! pops one. Keeping those frames in our own [`Stack`] instead turns the
+//! recursion into a loop, and running out of room becomes a recoverable
+//! error instead of a crash.
+
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use crate::{CapacityError, Stack};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Peg {
+    A,
+    B,
+    C,
+}
+
+impl fmt::Display for Peg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::A => f.write_str("A"),
+            Self::B => f.write_str("B"),
+            Self::C => f.write_str("C"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub disk: u32,
+    pub from: Peg,
+    pub to: Peg,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Move disk {} from {} to {}",
+            self.disk, self.from, self.to
+        )
+    }
+}
+
+/// The three pegs of a Tower of Hanoi, each one a [`Stack`] of disks.
+pub struct Hanoi<const SIZE: usize> {
+    pegs: [Stack<u32, SIZE>; 3],
+}
+
+impl<const SIZE: usize> Hanoi<SIZE> {
+    /// The most disks [`solve`](Self::solve) can count the moves for in a
+    /// `u64`.
+    pub const MAX_DISKS: u32 = u64::BITS - 1;
+
+    /// Puts `disks` disks on peg `A`, the largest at the bottom.
+    ///
+    /// More than [`MAX_DISKS`](Self::MAX_DISKS) don't fit either, whatever
+    /// `SIZE` is, the error then holds the largest disk.
+    pub fn new(disks: u32) -> Result<Self, CapacityError<u32>> {
+        if disks > Self::MAX_DISKS {
+            return Err(CapacityError::new(disks));
+        }
+        let mut pegs = [Stack::new(), Stack::new(), Stack::new()];
+        pegs[0].try_extend((1..=disks).rev())?;
+        Ok(Self { pegs })
+    }
+    pub fn peg(&self, peg: Peg) -> &Stack<u32, SIZE> {
+        &self.pegs[peg as usize]
+    }
+    /// Makes the only legal move between two pegs (the smaller top disk
+    /// goes onto the other peg).
+    fn move_between(&mut self, x: Peg, y: Peg) -> Move {
+        let (from, to) = match (self.peg(x).peek(), self.peg(y).peek()) {
+            (None, _) => (y, x),
+            (Some(_), None) => (x, y),
+            (Some(a), Some(b)) if a < b => (x, y),
+            _ => (y, x),
+        };
+        let disk = self.pegs[from as usize].pop().unwrap();
+        // the destination held a larger disk, so it has room for this one
+        self.pegs[to as usize].push(disk).unwrap();
+        Move { disk, from, to }
+    }
+    /// Moves every disk from `A` to `C` without recursion.
+    ///
+    /// The pegs are visited in a fixed cycle, on each step the only legal
+    /// move between the two pegs of that step is made. `2^n - 1` moves,
+    /// `O(2^n)` time and `O(1)` extra space besides the move log.
+    pub fn solve(&mut self) -> Vec<Move> {
+        let disks = self.peg(Peg::A).len() as u32;
+        let total = (1u64 << disks) - 1;
+        let cycle = if disks.is_multiple_of(2) {
+            [(Peg::A, Peg::B), (Peg::A, Peg::C), (Peg::B, Peg::C)]
+        } else {
+            [(Peg::A, Peg::C), (Peg::A, Peg::B), (Peg::B, Peg::C)]
+        };
+        (0..total)
+            .map(|i| {
+                let (x, y) = cycle[(i % 3) as usize];
+                self.move_between(x, y)
+            })
+            .collect()
+    }
+}
+
+/// What one call of a tree shaped recursion does.
+pub enum Call<'a, A, R> {
+    /// Base case, returns without calling anything.
+    Done(R),
+    /// Calls itself once for each argument, then combines the results
+    /// (given in the same order as the arguments).
+    Recurse(Vec<A>, Box<dyn FnOnce(Vec<R>) -> R + 'a>),
+}
+
+/// Reported to the tracer of [`run_traced`].
+pub enum CallEvent<'e, A, R> {
+    Push {
+        depth: usize,
+        arg: &'e A,
+    },
+    Pop {
+        depth: usize,
+        arg: &'e A,
+        result: &'e R,
+    },
+}
+
+struct Frame<'a, A, R> {
+    arg: A,
+    /// Arguments still to be called, the next one at the end.
+    pending: Vec<A>,
+    results: Vec<R>,
+    combine: Box<dyn FnOnce(Vec<R>) -> R + 'a>,
+}
+
+/// Runs a recursion described by `call` with its frames on an explicit
+/// [`Stack`] of `SIZE` frames.
+///
+/// If the recursion goes deeper than `SIZE`, the argument of the call that
+/// didn't fit is returned as the error (our own "stack overflow").
+pub fn run<'a, const SIZE: usize, A, R, F>(root: A, call: F) -> Result<R, CapacityError<A>>
+where
+    F: FnMut(&A) -> Call<'a, A, R>,
+{
+    run_traced::<SIZE, _, _, _, _>(root, call, |_| {})
+}
+
+/// Same as [`run`], but `trace` sees every frame as it is pushed and popped.
+pub fn run_traced<'a, const SIZE: usize, A, R, F, T>(
+    root: A,
+    mut call: F,
+    mut trace: T,
+) -> Result<R, CapacityError<A>>
+where
+    F: FnMut(&A) -> Call<'a, A, R>,
+    T: FnMut(CallEvent<'_, A, R>),
+{
+    let mut stack: Stack<Frame<'a, A, R>, SIZE> = Stack::new();
+    let mut arg = root;
+    loop {
+        let depth = stack.len();
+        trace(CallEvent::Push { depth, arg: &arg });
+        let mut returned = match call(&arg) {
+            Call::Done(result) => {
+                trace(CallEvent::Pop {
+                    depth,
+                    arg: &arg,
+                    result: &result,
+                });
+                Some(result)
+            }
+            Call::Recurse(mut args, combine) => {
+                args.reverse();
+                let frame = Frame {
+                    arg,
+                    pending: args,
+                    results: Vec::new(),
+                    combine,
+                };
+                if let Err(err) = stack.push(frame) {
+                    return Err(CapacityError::new(err.element().arg));
+                }
+                None
+            }
+        };
+        // hand the result to the caller, popping every frame that has
+        // nothing left to call until one has a pending argument
+        loop {
+            let Some(frame) = stack.peek_mut() else {
+                return Ok(returned.unwrap());
+            };
+            if let Some(result) = returned.take() {
+                frame.results.push(result);
+            }
+            if let Some(next) = frame.pending.pop() {
+                arg = next;
+                break;
+            }
+            let frame = stack.pop().unwrap();
+            let result = (frame.combine)(frame.results);
+            trace(CallEvent::Pop {
+                depth: stack.len(),
+                arg: &frame.arg,
+                result: &result,
+            });
+            returned = Some(result);
+        }
+    }
+}
+
+/// `n!`, a linear recursion: every call makes at most one more call.
+pub fn factorial<const SIZE: usize>(n: u64) -> Result<u64, CapacityError<u64>> {
+    run::<SIZE, _, _, _>(n, |&n| {
+        if n <= 1 {
+            Call::Done(1)
+        } else {
+            Call::Recurse(vec![n - 1], Box::new(move |r| n * r[0]))
+        }
+    })
+}
+
+fn fibonacci_call(n: &u64) -> Call<'static, u64, u64> {
+    if *n < 2 {
+        Call::Done(*n)
+    } else {
+        Call::Recurse(vec![n - 1, n - 2], Box::new(|r| r[0] + r[1]))
+    }
+}
+
+/// The naive (exponential) Fibonacci, whose calls form a binary tree.
+pub fn fibonacci<const SIZE: usize>(n: u64) -> Result<u64, CapacityError<u64>> {
+    run::<SIZE, _, _, _>(n, fibonacci_call)
+}
+
+/// Fills the region of cells equal to `grid[row][col]` with `color` and
+/// returns the number of cells filled.
+///
+/// Each call fans out to the four neighbours, so the recursion can get as
+/// deep as the region is large, which is why `SIZE` has to be chosen with
+/// the grid size in mind.
+pub fn flood_fill<const SIZE: usize, T: PartialEq + Clone>(
+    grid: &mut [Vec<T>],
+    row: usize,
+    col: usize,
+    color: T,
+) -> Result<usize, CapacityError<(usize, usize)>> {
+    let Some(target) = grid.get(row).and_then(|r| r.get(col)).cloned() else {
+        return Ok(0);
+    };
+    if target == color {
+        return Ok(0);
+    }
+    run::<SIZE, _, _, _>((row, col), |&(r, c)| {
+        match grid.get_mut(r).and_then(|row| row.get_mut(c)) {
+            Some(cell) if *cell == target => *cell = color.clone(),
+            _ => return Call::Done(0),
+        }
+        let mut next = vec![(r + 1, c), (r, c + 1)];
+        if r > 0 {
+            next.push((r - 1, c));
+        }
+        if c > 0 {
+            next.push((r, c - 1));
+        }
+        Call::Recurse(next, Box::new(|filled| 1 + filled.iter().sum::<usize>()))
+    })
+}
+
+/// Prints call frames as they are pushed and popped, indented by depth:
+///
+/// ```text
+/// -> fib(2)
+/// |  -> fib(1)
+/// |  <- fib(1) = 1
+/// |  -> fib(0)
+/// |  <- fib(0) = 0
+/// <- fib(2) = 1
+/// ```
+pub struct CallStackVisualizer<W: Write> {
+    name: String,
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> CallStackVisualizer<W> {
+    pub fn new<S: Into<String>>(name: S, out: W) -> Self {
+        Self {
+            name: name.into(),
+            out,
+            error: None,
+        }
+    }
+    pub fn record<A: fmt::Debug, R: fmt::Debug>(&mut self, event: CallEvent<'_, A, R>) {
+        if self.error.is_some() {
+            return;
+        }
+        let written = match event {
+            CallEvent::Push { depth, arg } => writeln!(
+                self.out,
+                "{}-> {}({:?})",
+                "|  ".repeat(depth),
+                self.name,
+                arg
+            ),
+            CallEvent::Pop { depth, arg, result } => writeln!(
+                self.out,
+                "{}<- {}({:?}) = {:?}",
+                "|  ".repeat(depth),
+                self.name,
+                arg,
+                result
+            ),
+        };
+        self.error = written.err();
+    }
+    /// Returns the writer, or the first error that happened while writing.
+    pub fn finish(self) -> io::Result<W> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.out),
+        }
+    }
+}
+
+/// Runs the naive Fibonacci and prints its call stack to `out`.
+///
+/// Calls nested deeper than `SIZE` fail with an error of kind `Other`
+/// that wraps the [`CapacityError`].
+pub fn visualize_fibonacci<const SIZE: usize, W: Write>(n: u64, out: W) -> io::Result<W> {
+    let mut visualizer = CallStackVisualizer::new("fib", out);
+    let result = run_traced::<SIZE, _, _, _, _>(n, fibonacci_call, |e| visualizer.record(e));
+    if let Err(err) = result {
+        return Err(io::Error::other(err));
+    }
+    visualizer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{factorial, fibonacci, flood_fill, visualize_fibonacci, Hanoi, Move, Peg};
+
+    #[test]
+    fn test_hanoi() {
+        let mut hanoi = Hanoi::<8>::new(3).unwrap();
+        let moves = hanoi.solve();
+        assert_eq!(moves.len(), 7);
+        assert_eq!(
+            moves[0],
+            Move {
+                disk: 1,
+                from: Peg::A,
+                to: Peg::C
+            }
+        );
+        assert_eq!(moves[0].to_string(), "Move disk 1 from A to C");
+        assert!(hanoi.peg(Peg::A).is_empty());
+        assert!(hanoi.peg(Peg::B).is_empty());
+        assert_eq!(
+            hanoi.peg(Peg::C).iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        let mut hanoi = Hanoi::<8>::new(4).unwrap();
+        assert_eq!(hanoi.solve().len(), 15);
+        assert_eq!(hanoi.peg(Peg::C).len(), 4);
+        assert!(Hanoi::<2>::new(3).is_err());
+    }
+
+    #[test]
+    fn test_hanoi_limit() {
+        assert!(Hanoi::<64>::new(Hanoi::<64>::MAX_DISKS).is_ok());
+        assert_eq!(Hanoi::<64>::new(64).err().unwrap().element(), 64);
+        assert_eq!(Hanoi::<8>::new(u32::MAX).err().unwrap().element(), u32::MAX);
+    }
+
+    #[test]
+    fn test_factorial_fibonacci() {
+        assert_eq!(factorial::<32>(0).unwrap(), 1);
+        assert_eq!(factorial::<32>(10).unwrap(), 3628800);
+        assert_eq!(factorial::<4>(10).unwrap_err().element(), 6);
+        assert_eq!(fibonacci::<32>(0).unwrap(), 0);
+        assert_eq!(fibonacci::<32>(20).unwrap(), 6765);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mut grid = vec![vec![1, 1, 0, 0], vec![1, 0, 0, 1], vec![1, 1, 0, 1]];
+        assert_eq!(flood_fill::<64, _>(&mut grid, 0, 0, 7).unwrap(), 5);
+        assert_eq!(
+            grid,
+            vec![vec![7, 7, 0, 0], vec![7, 0, 0, 1], vec![7, 7, 0, 1],]
+        );
+        assert_eq!(flood_fill::<64, _>(&mut grid, 0, 0, 7).unwrap(), 0);
+        assert_eq!(flood_fill::<64, _>(&mut grid, 9, 9, 7).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_visualize_fibonacci() {
+        let out = visualize_fibonacci::<8, _>(2, Vec::new()).unwrap();
+        let expected = "\
+-> fib(2)
+|  -> fib(1)
+|  <- fib(1) = 1
+|  -> fib(0)
+|  <- fib(0) = 0
+<- fib(2) = 1
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+        // too deep for the stack
+        let err = visualize_fibonacci::<2, _>(5, Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        assert!(err
+            .get_ref()
+            .is_some_and(|inner| inner.is::<crate::CapacityError<u64>>()));
+    }
+}
@@ -0,0 +1,389 @@
+use std::{error, fmt};
+
+/// Error returned when an element is pushed onto a full [`Stack`].
+///
+/// The rejected element is handed back so the caller doesn't lose it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T> {
+    element: T,
+}
+
+impl<T> CapacityError<T> {
+    pub fn new(element: T) -> Self {
+        Self { element }
+    }
+    pub fn element(self) -> T {
+        self.element
+    }
+}
+
+impl<T> fmt::Debug for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CapacityError: stack is full")
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("stack is full")
+    }
+}
+
+impl<T> error::Error for CapacityError<T> {}
+
+/// A fixed capacity stack that lives entirely inline (no heap allocation).
+///
+/// `elements[0]` is the bottom of the stack and `elements[len - 1]` is the top.
+pub struct Stack<T, const SIZE: usize> {
+    elements: [Option<T>; SIZE],
+    len: usize,
+}
+
+impl<T, const SIZE: usize> Stack<T, SIZE> {
+    pub fn new() -> Self {
+        Self {
+            elements: std::array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        SIZE
+    }
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.len == SIZE
+    }
+    pub fn push(&mut self, value: T) -> Result<(), CapacityError<T>> {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+        self.elements[self.len] = Some(value);
+        self.len += 1;
+        Ok(())
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        self.elements[self.len].take()
+    }
+    pub fn peek(&self) -> Option<&T> {
+        self.len
+            .checked_sub(1)
+            .and_then(|i| self.elements[i].as_ref())
+    }
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.len
+            .checked_sub(1)
+            .and_then(|i| self.elements[i].as_mut())
+    }
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+    /// Iterates from the top of the stack to the bottom.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.elements[..self.len].iter().rev(),
+        }
+    }
+    /// Removes every element, yielding them from the top to the bottom.
+    pub fn drain(&mut self) -> Drain<'_, T, SIZE> {
+        Drain { stack: self }
+    }
+    /// Keeps only the elements for which `f` returns `true`.
+    ///
+    /// The relative order of the remaining elements is preserved.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            let keep = f(self.elements[i].as_ref().unwrap());
+            if keep {
+                self.elements.swap(kept, i);
+                kept += 1;
+            } else {
+                self.elements[i] = None;
+            }
+        }
+        self.len = kept;
+    }
+    /// Like [`FromIterator::from_iter`], but reports overflow instead of panicking.
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(iter: I) -> Result<Self, CapacityError<T>> {
+        let mut stack = Self::new();
+        stack.try_extend(iter)?;
+        Ok(stack)
+    }
+    /// Like [`Extend::extend`], but reports overflow instead of panicking.
+    pub fn try_extend<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), CapacityError<T>> {
+        for item in iter {
+            self.push(item)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, const SIZE: usize> Default for Stack<T, SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug, const SIZE: usize> fmt::Debug for Stack<T, SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone, const SIZE: usize> Clone for Stack<T, SIZE> {
+    fn clone(&self) -> Self {
+        Self {
+            elements: self.elements.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: PartialEq, const SIZE: usize> PartialEq for Stack<T, SIZE> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, const SIZE: usize> Eq for Stack<T, SIZE> {}
+
+/// # Panics
+///
+/// Panics if the iterator yields more than `SIZE` elements,
+/// use [`Stack::try_from_iter`] to handle that case.
+impl<T, const SIZE: usize> FromIterator<T> for Stack<T, SIZE> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        match Self::try_from_iter(iter) {
+            Ok(stack) => stack,
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
+
+/// # Panics
+///
+/// Panics if the stack overflows, use [`Stack::try_extend`] to handle that case.
+impl<T, const SIZE: usize> Extend<T> for Stack<T, SIZE> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        if let Err(err) = self.try_extend(iter) {
+            panic!("{}", err);
+        }
+    }
+}
+
+/// The last element of the array ends up on top of the stack.
+impl<T, const N: usize, const SIZE: usize> TryFrom<[T; N]> for Stack<T, SIZE> {
+    type Error = CapacityError<[T; N]>;
+    fn try_from(value: [T; N]) -> Result<Self, Self::Error> {
+        if N > SIZE {
+            return Err(CapacityError::new(value));
+        }
+        let mut stack = Self::new();
+        for item in value {
+            stack.elements[stack.len] = Some(item);
+            stack.len += 1;
+        }
+        Ok(stack)
+    }
+}
+
+pub struct Iter<'a, T> {
+    inner: std::iter::Rev<std::slice::Iter<'a, Option<T>>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().and_then(Option::as_ref)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IntoIter<T, const SIZE: usize> {
+    stack: Stack<T, SIZE>,
+}
+
+impl<T, const SIZE: usize> Iterator for IntoIter<T, SIZE> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stack.pop()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.stack.len, Some(self.stack.len))
+    }
+}
+
+impl<T, const SIZE: usize> ExactSizeIterator for IntoIter<T, SIZE> {}
+
+pub struct Drain<'a, T, const SIZE: usize> {
+    stack: &'a mut Stack<T, SIZE>,
+}
+
+impl<T, const SIZE: usize> Iterator for Drain<'_, T, SIZE> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stack.pop()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.stack.len, Some(self.stack.len))
+    }
+}
+
+impl<T, const SIZE: usize> ExactSizeIterator for Drain<'_, T, SIZE> {}
+
+impl<T, const SIZE: usize> Drop for Drain<'_, T, SIZE> {
+    fn drop(&mut self) {
+        self.stack.clear();
+    }
+}
+
+impl<T, const SIZE: usize> IntoIterator for Stack<T, SIZE> {
+    type Item = T;
+    type IntoIter = IntoIter<T, SIZE>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { stack: self }
+    }
+}
+
+impl<'a, T, const SIZE: usize> IntoIterator for &'a Stack<T, SIZE> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Serialized as a sequence from the bottom of the stack to the top,
+/// so deserializing pushes the elements back in the same order.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::{fmt, marker::PhantomData};
+
+    use serde::{
+        de::{self, SeqAccess, Visitor},
+        ser::SerializeSeq,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use super::Stack;
+
+    impl<T: Serialize, const SIZE: usize> Serialize for Stack<T, SIZE> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.len))?;
+            for item in self.elements[..self.len].iter().flatten() {
+                seq.serialize_element(item)?;
+            }
+            seq.end()
+        }
+    }
+
+    struct StackVisitor<T, const SIZE: usize>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>, const SIZE: usize> Visitor<'de> for StackVisitor<T, SIZE> {
+        type Value = Stack<T, SIZE>;
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a sequence of at most {} elements", SIZE)
+        }
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut stack = Stack::new();
+            while let Some(item) = seq.next_element()? {
+                if stack.push(item).is_err() {
+                    return Err(de::Error::invalid_length(SIZE + 1, &self));
+                }
+            }
+            Ok(stack)
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>, const SIZE: usize> Deserialize<'de> for Stack<T, SIZE> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(StackVisitor(PhantomData))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stack;
+
+    #[test]
+    fn test_push_pop() {
+        let mut stack = Stack::<i32, 3>::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.push(1), Ok(()));
+        assert_eq!(stack.push(2), Ok(()));
+        assert_eq!(stack.push(3), Ok(()));
+        assert_eq!(stack.push(4).unwrap_err().element(), 4);
+        assert_eq!(stack.peek(), Some(&3));
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+    }
+
+    #[test]
+    fn test_iter_lifo() {
+        let stack = Stack::<i32, 4>::try_from([1, 2, 3]).unwrap();
+        assert_eq!(stack.iter().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+        assert_eq!((&stack).into_iter().count(), 3);
+        assert_eq!(stack.into_iter().collect::<Vec<_>>(), vec![3, 2, 1]);
+        assert!(Stack::<i32, 2>::try_from([1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_from_iter_extend() {
+        let mut stack: Stack<i32, 5> = (1..=3).collect();
+        stack.extend([4, 5]);
+        assert_eq!(stack.len(), 5);
+        assert_eq!(stack.try_extend([6]).unwrap_err().element(), 6);
+        assert!(Stack::<i32, 2>::try_from_iter(1..=3).is_err());
+        assert_eq!(stack.clone(), stack);
+        assert_eq!(format!("{:?}", stack), "[5, 4, 3, 2, 1]");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_iter_overflow() {
+        let _: Stack<i32, 2> = (1..=3).collect();
+    }
+
+    #[test]
+    fn test_drain_retain() {
+        let mut stack = Stack::<i32, 6>::try_from([1, 2, 3, 4, 5, 6]).unwrap();
+        stack.retain(|v| v % 2 == 0);
+        assert_eq!(stack, Stack::try_from([2, 4, 6]).unwrap());
+        assert_eq!(stack.drain().collect::<Vec<_>>(), vec![6, 4, 2]);
+        assert!(stack.is_empty());
+        assert_eq!(Stack::<i32, 1>::default(), Stack::new());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let stack = Stack::<i32, 4>::try_from([1, 2, 3]).unwrap();
+        let json = serde_json::to_string(&stack).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        assert_eq!(serde_json::from_str::<Stack<i32, 4>>(&json).unwrap(), stack);
+        assert!(serde_json::from_str::<Stack<i32, 2>>(&json).is_err());
+    }
+}